@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS notify_snapshots;
+DROP TABLE IF EXISTS scan_event_attempts;
+DROP TABLE IF EXISTS scan_event_targets;
+DROP TABLE IF EXISTS app_state;
+DROP TABLE IF EXISTS scan_events;
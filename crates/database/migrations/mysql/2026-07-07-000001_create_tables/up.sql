@@ -0,0 +1,95 @@
+-- MySQL starts from the schema the other backends reached through their own
+-- migrations. Timestamps are DATETIME(6) as TIMESTAMP stops at 2038, and keys
+-- are VARCHAR as TEXT columns cannot be indexed in full.
+CREATE TABLE IF NOT EXISTS scan_events (
+    id VARCHAR(64) PRIMARY KEY NOT NULL,
+
+    event_source VARCHAR(255) NOT NULL,
+    event_timestamp DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+
+    file_path TEXT NOT NULL,
+    file_hash TEXT,
+
+    process_status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    found_status VARCHAR(32) NOT NULL DEFAULT 'not_found',
+
+    failed_times INTEGER NOT NULL DEFAULT 0,
+    next_retry_at DATETIME(6),
+
+    found_at DATETIME(6),
+    processed_at DATETIME(6),
+
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+
+    can_process DATETIME(6) NOT NULL DEFAULT '2024-10-14 12:00:00',
+
+    event_kind VARCHAR(32) NOT NULL DEFAULT 'modified',
+    previous_path TEXT,
+
+    claimed_by VARCHAR(64),
+    claimed_until DATETIME(6),
+
+    -- Stands in for the partial unique index on queued paths the other
+    -- backends use, NULL (and so never a duplicate) once an event is done.
+    queued_path_hash BINARY(32) AS (
+        CASE WHEN process_status IN ('pending', 'retry') THEN UNHEX(SHA2(file_path, 256)) END
+    ) STORED,
+
+    UNIQUE KEY idx_scan_events_dedupe_pending_retry (queued_path_hash),
+    KEY idx_scan_events_file_path_process_status (file_path(255), process_status),
+    KEY idx_scan_events_process_status_next_retry_at_can_process (process_status, next_retry_at, can_process),
+    KEY idx_scan_events_found_status (found_status),
+    KEY idx_scan_events_process_found (process_status, found_status)
+);
+
+CREATE TABLE IF NOT EXISTS app_state (
+    `key` VARCHAR(255) PRIMARY KEY NOT NULL,
+    value LONGBLOB NOT NULL,
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6)
+);
+
+CREATE TABLE IF NOT EXISTS scan_event_targets (
+    event_id VARCHAR(64) NOT NULL,
+    target VARCHAR(255) NOT NULL,
+
+    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_retry_at DATETIME(6),
+    last_error TEXT,
+
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+
+    PRIMARY KEY (event_id, target),
+    FOREIGN KEY (event_id) REFERENCES scan_events (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS scan_event_attempts (
+    id VARCHAR(64) PRIMARY KEY NOT NULL,
+    event_id VARCHAR(64) NOT NULL,
+    target VARCHAR(255) NOT NULL,
+
+    success BOOLEAN NOT NULL,
+    http_status INTEGER,
+    error TEXT,
+    duration_ms BIGINT NOT NULL,
+
+    attempted_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+
+    KEY idx_scan_event_attempts_event_id_attempted_at (event_id, attempted_at),
+    FOREIGN KEY (event_id) REFERENCES scan_events (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS notify_snapshots (
+    trigger_name VARCHAR(255) NOT NULL,
+    path TEXT NOT NULL,
+
+    size BIGINT NOT NULL,
+    modified DATETIME(6) NOT NULL,
+
+    -- Paths can be longer than an index allows
+    path_hash BINARY(32) AS (UNHEX(SHA2(path, 256))) STORED,
+
+    PRIMARY KEY (trigger_name, path_hash)
+);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS scan_event_daily_counts;
@@ -0,0 +1,11 @@
+-- Number of events created each day by source and final status, kept once the
+-- events themselves are pruned by the retention policy.
+CREATE TABLE IF NOT EXISTS scan_event_daily_counts (
+    day DATE NOT NULL,
+    event_source VARCHAR(255) NOT NULL,
+    process_status VARCHAR(32) NOT NULL,
+
+    events INTEGER NOT NULL,
+
+    PRIMARY KEY (day, event_source, process_status)
+);
@@ -0,0 +1,14 @@
+ALTER TABLE scan_events ADD COLUMN targets_hit TEXT DEFAULT '' NOT NULL;
+
+UPDATE scan_events
+SET targets_hit = COALESCE(
+  (
+    SELECT string_agg(target, ',' ORDER BY target)
+    FROM scan_event_targets
+    WHERE scan_event_targets.event_id = scan_events.id
+      AND scan_event_targets.status = 'complete'
+  ),
+  ''
+);
+
+DROP TABLE IF EXISTS scan_event_targets;
@@ -0,0 +1,28 @@
+-- One row per (event, target) so each target keeps its own retry schedule
+-- instead of sharing the event's single `failed_times` counter.
+CREATE TABLE IF NOT EXISTS scan_event_targets (
+    event_id TEXT NOT NULL REFERENCES scan_events (id) ON DELETE CASCADE,
+    target TEXT NOT NULL,
+
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER DEFAULT 0 NOT NULL,
+    next_retry_at TIMESTAMP,
+    last_error TEXT,
+
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    PRIMARY KEY (event_id, target)
+);
+
+-- Every name in `targets_hit` was a completed delivery.
+INSERT INTO scan_event_targets (
+    event_id, target, status, attempts, created_at, updated_at
+)
+SELECT scan_events.id, split.target, 'complete', 1, scan_events.updated_at, scan_events.updated_at
+FROM scan_events
+CROSS JOIN LATERAL unnest(string_to_array(scan_events.targets_hit, ',')) AS split (target)
+WHERE split.target <> ''
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE scan_events DROP COLUMN targets_hit;
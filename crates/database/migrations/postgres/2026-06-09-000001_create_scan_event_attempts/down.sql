@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_scan_event_attempts_event_id_attempted_at;
+DROP TABLE IF EXISTS scan_event_attempts;
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS scan_event_attempts (
+    id TEXT PRIMARY KEY NOT NULL,
+    event_id TEXT NOT NULL REFERENCES scan_events (id) ON DELETE CASCADE,
+    target TEXT NOT NULL,
+
+    success BOOLEAN NOT NULL,
+    http_status INTEGER,
+    error TEXT,
+    duration_ms BIGINT NOT NULL,
+
+    attempted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_scan_event_attempts_event_id_attempted_at
+ON scan_event_attempts (event_id, attempted_at);
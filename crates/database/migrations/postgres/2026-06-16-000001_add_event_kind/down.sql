@@ -0,0 +1,2 @@
+ALTER TABLE scan_events DROP COLUMN previous_path;
+ALTER TABLE scan_events DROP COLUMN event_kind;
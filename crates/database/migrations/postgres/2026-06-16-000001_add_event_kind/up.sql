@@ -0,0 +1,2 @@
+ALTER TABLE scan_events ADD COLUMN event_kind TEXT NOT NULL DEFAULT 'modified';
+ALTER TABLE scan_events ADD COLUMN previous_path TEXT;
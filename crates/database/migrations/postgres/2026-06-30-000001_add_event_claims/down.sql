@@ -0,0 +1,2 @@
+ALTER TABLE scan_events DROP COLUMN claimed_until;
+ALTER TABLE scan_events DROP COLUMN claimed_by;
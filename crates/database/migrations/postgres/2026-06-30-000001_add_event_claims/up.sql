@@ -0,0 +1,3 @@
+-- Lease on an event held by the instance dispatching it, only written on Postgres
+ALTER TABLE scan_events ADD COLUMN claimed_by TEXT;
+ALTER TABLE scan_events ADD COLUMN claimed_until TIMESTAMP;
@@ -0,0 +1,18 @@
+ALTER TABLE scan_events ADD COLUMN targets_hit TEXT DEFAULT '' NOT NULL;
+
+UPDATE scan_events
+SET targets_hit = COALESCE(
+  (
+    SELECT group_concat(target, ',')
+    FROM (
+      SELECT target
+      FROM scan_event_targets
+      WHERE scan_event_targets.event_id = scan_events.id
+        AND scan_event_targets.status = 'complete'
+      ORDER BY target
+    )
+  ),
+  ''
+);
+
+DROP TABLE IF EXISTS scan_event_targets;
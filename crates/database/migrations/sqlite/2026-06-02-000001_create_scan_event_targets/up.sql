@@ -0,0 +1,39 @@
+-- One row per (event, target) so each target keeps its own retry schedule
+-- instead of sharing the event's single `failed_times` counter.
+CREATE TABLE IF NOT EXISTS scan_event_targets (
+    event_id TEXT NOT NULL REFERENCES scan_events (id) ON DELETE CASCADE,
+    target TEXT NOT NULL,
+
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER DEFAULT 0 NOT NULL,
+    next_retry_at TIMESTAMP,
+    last_error TEXT,
+
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    PRIMARY KEY (event_id, target)
+);
+
+-- Every name in `targets_hit` was a completed delivery.
+WITH RECURSIVE split (event_id, target, rest, stamp) AS (
+    SELECT id, '', targets_hit || ',', updated_at
+    FROM scan_events
+    WHERE targets_hit <> ''
+  UNION ALL
+    SELECT
+      event_id,
+      substr(rest, 1, instr(rest, ',') - 1),
+      substr(rest, instr(rest, ',') + 1),
+      stamp
+    FROM split
+    WHERE rest <> ''
+)
+INSERT OR IGNORE INTO scan_event_targets (
+    event_id, target, status, attempts, created_at, updated_at
+)
+SELECT event_id, target, 'complete', 1, stamp, stamp
+FROM split
+WHERE target <> '';
+
+ALTER TABLE scan_events DROP COLUMN targets_hit;
@@ -0,0 +1,11 @@
+-- Last known state of the files under each notify trigger, so changes made
+-- while autopulse was not running can be picked up on startup.
+CREATE TABLE IF NOT EXISTS notify_snapshots (
+    trigger_name TEXT NOT NULL,
+    path TEXT NOT NULL,
+
+    size BIGINT NOT NULL,
+    modified TIMESTAMP NOT NULL,
+
+    PRIMARY KEY (trigger_name, path)
+);
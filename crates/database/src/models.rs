@@ -1,5 +1,5 @@
 use autopulse_utils::{generate_uuid, Rewrite};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 use serde::Serialize;
 use std::{fmt::Display, str::FromStr};
@@ -21,6 +21,8 @@ pub enum FoundStatus {
     Found,
     NotFound,
     HashMismatch,
+    /// The file exists but is still changing, see [`opts.stability`](autopulse_service::settings::opts::Opts::stability).
+    Unstable,
 }
 
 impl Display for FoundStatus {
@@ -29,6 +31,7 @@ impl Display for FoundStatus {
             Self::Found => "found",
             Self::NotFound => "not_found",
             Self::HashMismatch => "hash_mismatch",
+            Self::Unstable => "unstable",
         };
 
         write!(f, "{status}")
@@ -71,11 +74,67 @@ impl FromStr for ProcessStatus {
     }
 }
 
+/// What happened to the file behind a [`ScanEvent`].
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    #[default]
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl From<EventKind> for &'static str {
+    fn from(val: EventKind) -> Self {
+        match val {
+            EventKind::Created => "created",
+            EventKind::Modified => "modified",
+            EventKind::Deleted => "deleted",
+            EventKind::Renamed => "renamed",
+        }
+    }
+}
+
+impl From<EventKind> for String {
+    fn from(val: EventKind) -> Self {
+        <&'static str>::from(val).to_string()
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <&'static str>::from(*self))
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(Self::Created),
+            "modified" => Ok(Self::Modified),
+            "deleted" => Ok(Self::Deleted),
+            "renamed" => Ok(Self::Renamed),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Represents a scan event.
 ///
 /// A scan event is created when a file is added by [Triggers](crate::service::triggers).
 #[derive(
-    Queryable, Selectable, Serialize, Clone, Debug, AsChangeset, Identifiable, Hash, Eq, PartialEq,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Clone,
+    Debug,
+    AsChangeset,
+    Identifiable,
+    Hash,
+    Eq,
+    PartialEq,
 )]
 #[diesel(table_name = crate::schema::scan_events)]
 pub struct ScanEvent {
@@ -101,9 +160,6 @@ pub struct ScanEvent {
     /// The time the scan event will be retried.
     pub next_retry_at: Option<chrono::NaiveDateTime>,
 
-    /// The targets that have been hit by the scan event delimited by a comma.
-    pub targets_hit: String,
-
     /// The time the file was found.
     pub found_at: Option<chrono::NaiveDateTime>,
     /// The time the scan event was processed.
@@ -116,23 +172,22 @@ pub struct ScanEvent {
 
     /// The time the scan event can be processed.
     pub can_process: NaiveDateTime,
+
+    /// The [`EventKind`] reported by the trigger.
+    pub event_kind: String,
+    /// The path the file had before it was renamed, if the trigger reported one.
+    pub previous_path: Option<String>,
+
+    /// The instance dispatching the scan event, only set on Postgres.
+    pub claimed_by: Option<String>,
+    /// The time the claim lapses and another instance may take the scan event over.
+    pub claimed_until: Option<NaiveDateTime>,
 }
 
 impl ScanEvent {
-    pub fn get_targets_hit(&self) -> Vec<String> {
-        self.targets_hit
-            .split(',')
-            .map(|s| s.to_string())
-            .filter(|s| !s.is_empty())
-            .collect()
-    }
-
-    pub fn add_target_hit(&mut self, target: &str) {
-        let mut targets = self.get_targets_hit();
-        targets.push(target.to_string());
-        targets.sort();
-        targets.dedup();
-        self.targets_hit = targets.join(",");
+    /// The [`EventKind`] of the event, falling back to [`EventKind::Modified`] for unknown values.
+    pub fn kind(&self) -> EventKind {
+        EventKind::from_str(&self.event_kind).unwrap_or_default()
     }
 
     pub fn get_path(&self, rewrite: &Option<Rewrite>) -> String {
@@ -143,6 +198,98 @@ impl ScanEvent {
     }
 }
 
+/// The delivery state of a [`ScanEvent`] for a single target.
+///
+/// Each target retries on its own schedule; the parent event is only
+/// complete once every target that applies to it has completed.
+#[derive(
+    Queryable,
+    Selectable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+    Serialize,
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+)]
+#[diesel(table_name = crate::schema::scan_event_targets)]
+#[diesel(primary_key(event_id, target))]
+#[diesel(belongs_to(ScanEvent, foreign_key = event_id))]
+pub struct ScanEventTarget {
+    /// The id of the [`ScanEvent`] being delivered.
+    pub event_id: String,
+    /// The name of the target as configured in `targets`.
+    pub target: String,
+
+    /// The [`ProcessStatus`] of this delivery.
+    pub status: String,
+    /// The number of delivery attempts made. Limited to [`opts.max_retries`](/autopulse_service/settings/opts/struct.Opts.html#structfield.max_retries).
+    pub attempts: i32,
+    /// The time this delivery will be retried.
+    pub next_retry_at: Option<NaiveDateTime>,
+    /// The error returned by the last failed attempt.
+    pub last_error: Option<String>,
+
+    /// The time the first attempt was made.
+    pub created_at: NaiveDateTime,
+    /// The time the last attempt was made.
+    pub updated_at: NaiveDateTime,
+}
+
+impl ScanEventTarget {
+    pub fn new(event_id: &str, target: &str, now: NaiveDateTime) -> Self {
+        Self {
+            event_id: event_id.to_string(),
+            target: target.to_string(),
+            status: ProcessStatus::Pending.into(),
+            attempts: 0,
+            next_retry_at: None,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Whether the target should be attempted at `now`.
+    pub fn is_due(&self, now: NaiveDateTime) -> bool {
+        match ProcessStatus::from_str(&self.status) {
+            Ok(ProcessStatus::Pending | ProcessStatus::Retry) => {
+                self.next_retry_at.is_none_or(|at| at <= now)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A single delivery attempt of a [`ScanEvent`] to a target.
+#[derive(Queryable, Selectable, Insertable, Associations, Serialize, Clone, Debug)]
+#[diesel(table_name = crate::schema::scan_event_attempts)]
+#[diesel(belongs_to(ScanEvent, foreign_key = event_id))]
+pub struct ScanEventAttempt {
+    /// The [uuid](crate::utils::generate_uuid::generate_uuid) of the attempt.
+    pub id: String,
+    /// The id of the [`ScanEvent`] being delivered.
+    pub event_id: String,
+    /// The name of the target as configured in `targets`.
+    pub target: String,
+
+    /// Whether the target processed the file.
+    pub success: bool,
+    /// The HTTP status the target answered with, if the failure was an HTTP response.
+    pub http_status: Option<i32>,
+    /// The error chain of a failed attempt.
+    pub error: Option<String>,
+    /// How long the target took, in milliseconds. Targets process events in
+    /// batches, so every event in a batch shares the batch duration.
+    pub duration_ms: i64,
+
+    /// The time the attempt finished.
+    pub attempted_at: NaiveDateTime,
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::scan_events)]
 #[doc(hidden)]
@@ -155,6 +302,9 @@ pub struct NewScanEvent {
 
     pub found_status: String,
     pub can_process: NaiveDateTime,
+
+    pub event_kind: String,
+    pub previous_path: Option<String>,
 }
 
 impl Default for NewScanEvent {
@@ -166,11 +316,28 @@ impl Default for NewScanEvent {
             file_hash: None,
             found_status: FoundStatus::NotFound.into(),
             can_process: chrono::Utc::now().naive_utc(),
+            event_kind: EventKind::default().into(),
+            previous_path: None,
         }
     }
 }
 
-/// Key/value store; currently holds only the UI session-signing key.
+/// Events created on a day, by source and final status, kept after the events themselves are pruned.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::scan_event_daily_counts)]
+#[diesel(primary_key(day, event_source, process_status))]
+pub struct ScanEventDailyCount {
+    /// The day the events were created, in UTC.
+    pub day: NaiveDate,
+    /// The name of the Trigger that created the events.
+    pub event_source: String,
+    /// The [`ProcessStatus`] the events had when they were pruned.
+    pub process_status: String,
+    /// The number of events.
+    pub events: i32,
+}
+
+/// Key/value store for the UI session-signing key and the time of each trigger's last sweep.
 #[derive(Queryable, Selectable, Insertable, Clone, Debug)]
 #[diesel(table_name = crate::schema::app_state)]
 pub struct AppState {
@@ -178,3 +345,15 @@ pub struct AppState {
     pub value: Vec<u8>,
     pub updated_at: NaiveDateTime,
 }
+
+/// Last known size and modified time of a file watched by a notify trigger.
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Clone, Debug, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::notify_snapshots)]
+#[diesel(primary_key(trigger_name, path))]
+pub struct NotifySnapshot {
+    pub trigger_name: String,
+    /// The path as watched, before any rewrite.
+    pub path: String,
+    pub size: i64,
+    pub modified: NaiveDateTime,
+}
@@ -11,12 +11,15 @@ diesel::table! {
         found_status -> Text,
         failed_times -> Integer,
         next_retry_at -> Nullable<Timestamp>,
-        targets_hit -> Text,
         found_at -> Nullable<Timestamp>,
         processed_at -> Nullable<Timestamp>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
         can_process -> Timestamp,
+        event_kind -> Text,
+        previous_path -> Nullable<Text>,
+        claimed_by -> Nullable<Text>,
+        claimed_until -> Nullable<Timestamp>,
     }
 }
 
@@ -27,3 +30,59 @@ diesel::table! {
         updated_at -> Timestamp,
     }
 }
+
+diesel::table! {
+    scan_event_targets (event_id, target) {
+        event_id -> Text,
+        target -> Text,
+        status -> Text,
+        attempts -> Integer,
+        next_retry_at -> Nullable<Timestamp>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    scan_event_attempts (id) {
+        id -> Text,
+        event_id -> Text,
+        target -> Text,
+        success -> Bool,
+        http_status -> Nullable<Integer>,
+        error -> Nullable<Text>,
+        duration_ms -> BigInt,
+        attempted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    notify_snapshots (trigger_name, path) {
+        trigger_name -> Text,
+        path -> Text,
+        size -> BigInt,
+        modified -> Timestamp,
+    }
+}
+
+diesel::table! {
+    scan_event_daily_counts (day, event_source, process_status) {
+        day -> Date,
+        event_source -> Text,
+        process_status -> Text,
+        events -> Integer,
+    }
+}
+
+diesel::joinable!(scan_event_targets -> scan_events (event_id));
+diesel::joinable!(scan_event_attempts -> scan_events (event_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    app_state,
+    notify_snapshots,
+    scan_event_attempts,
+    scan_event_daily_counts,
+    scan_event_targets,
+    scan_events,
+);
@@ -9,8 +9,8 @@ use actix_web::{
 use actix_web_httpauth::extractors::basic;
 use autopulse_service::manager::PulseManager;
 use routes::{
-    config::config_template, index::hello, list::list, login::login, stats::stats, status::status,
-    triggers::trigger_get, triggers::trigger_post,
+    config::config_template, index::hello, list::list, login::login, metrics::metrics,
+    stats::stats, status::status, targets::targets, triggers::trigger_get, triggers::trigger_post,
 };
 
 pub mod routes;
@@ -44,8 +44,10 @@ pub fn get_server(hostname: &str, port: &u16, manager: PulseManager) -> anyhow::
             .service(trigger_post)
             .service(status)
             .service(stats)
+            .service(metrics)
             .service(login)
             .service(list)
+            .service(targets)
             .service(config_template)
             .app_data(basic::Config::default().realm("Restricted area"))
             .app_data(login_limiter.clone())
@@ -71,7 +73,9 @@ mod tests {
 
     #[cfg(feature = "sqlite")]
     mod routes {
+        mod metrics;
         mod public_endpoints;
+        mod targets;
         mod triggers;
     }
 }
@@ -143,6 +143,9 @@ fn generate_config_template(
                 TriggerType::Notify => {
                     Trigger::Notify(serde_json::from_str(r#"{"paths": ["/media"]}"#)?)
                 }
+                TriggerType::Sweep => Trigger::Sweep(serde_json::from_str(
+                    r#"{"schedule": "0 */6 * * *", "paths": ["/media"]}"#,
+                )?),
             },
         );
     }
@@ -191,6 +194,24 @@ fn generate_config_template(
                 TargetType::Audiobookshelf => Target::Audiobookshelf(serde_json::from_str(
                     r#"{"url": "{url}", "token": "{token}"}"#,
                 )?),
+                TargetType::Kodi => Target::Kodi(serde_json::from_str(r#"{"url": "{url}"}"#)?),
+                TargetType::Subsonic => Target::Subsonic(serde_json::from_str(
+                    r#"{"url": "{url}", "username": "{username}", "password": "{password}"}"#,
+                )?),
+                TargetType::Komga => Target::Komga(serde_json::from_str(
+                    r#"{"url": "{url}", "token": "{token}"}"#,
+                )?),
+                TargetType::Kavita => Target::Kavita(serde_json::from_str(
+                    r#"{"url": "{url}", "token": "{token}"}"#,
+                )?),
+                TargetType::Lidarr => Target::Lidarr(serde_json::from_str(
+                    r#"{"url": "{url}", "token": "{token}"}"#,
+                )?),
+                TargetType::Readarr => Target::Readarr(serde_json::from_str(
+                    r#"{"url": "{url}", "token": "{token}"}"#,
+                )?),
+                TargetType::Rclone => Target::Rclone(serde_json::from_str(r#"{"url": "{url}"}"#)?),
+                TargetType::Http => Target::Http(serde_json::from_str(r#"{"url": "{url}"}"#)?),
             },
         );
     }
@@ -0,0 +1,25 @@
+use crate::middleware::auth::AuthenticatedUser;
+use actix_web::{get, web::Data, HttpResponse, Responder, Result};
+use autopulse_service::manager::PulseManager;
+use tracing::error;
+
+#[doc(hidden)]
+#[get("/metrics")]
+pub async fn metrics(
+    manager: Data<PulseManager>,
+    auth: Option<AuthenticatedUser>,
+) -> Result<impl Responder> {
+    if auth.is_none() && !manager.settings.auth.public_metrics {
+        return Ok(HttpResponse::Unauthorized().json("Authentication required"));
+    }
+
+    match manager.metrics.encode() {
+        Ok(body) => Ok(HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(body)),
+        Err(e) => {
+            error!("failed to encode metrics: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
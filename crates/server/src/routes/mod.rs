@@ -39,6 +39,18 @@ pub mod status;
 /// - **200 OK**: Returns a [`StatsResponse`](stats::StatsResponse) object containing the service statistics and response time.
 pub mod stats;
 
+/// GET &nbsp; - `/metrics`
+///
+/// Prometheus metrics in the text exposition format.
+///
+/// Note: Requires authentication unless [`auth.public_metrics`](autopulse_service::settings::auth::Auth::public_metrics) is set.
+///
+/// # Responses
+///
+/// - **200 OK**: Returns the metrics.
+/// - **401 Unauthorized**: Returned if the request is not authenticated.
+pub mod metrics;
+
 /// GET &nbsp; - `/list?status={status}&page={page}&limit={limit}&sort={sort}`
 ///
 /// Returns a list of scan events from the database.
@@ -58,6 +70,35 @@ pub mod stats;
 /// - **401 Unauthorized**: Returned if the request is not authenticated.
 pub mod list;
 
+/// GET &nbsp; - `/targets`
+///
+/// Returns the circuit breaker health of every configured target, sorted by name.
+///
+/// Note: Requires authentication.
+///
+/// # Responses
+///
+/// - **200 OK**: Returns a list of [`TargetStatus`](autopulse_service::manager::TargetStatus) objects.
+/// - **401 Unauthorized**: Returned if the request is not authenticated.
+///
+/// # Example Response
+///
+/// ```json
+/// [
+///   {
+///     "name": "my_plex",
+///     "type": "plex",
+///     "state": "open",
+///     "consecutive_failures": 5,
+///     "retry_at": "2024-01-01T00:01:00",
+///     "last_error": "error sending request",
+///     "last_success_at": null,
+///     "last_failure_at": "2024-01-01T00:00:00"
+///   }
+/// ]
+/// ```
+pub mod targets;
+
 /// POST - `/login`
 ///
 /// Authenticates the user with the provided credentials and returns ok if successful.
@@ -4,7 +4,18 @@ use actix_web::{
     web::{Data, Path},
     HttpResponse, Responder, Result,
 };
+use autopulse_database::models::{ScanEvent, ScanEventAttempt, ScanEventTarget};
 use autopulse_service::manager::PulseManager;
+use serde::Serialize;
+
+/// A scan event alongside its per-target delivery state and attempt history.
+#[derive(Serialize)]
+struct EventStatus {
+    #[serde(flatten)]
+    event: ScanEvent,
+    targets: Vec<ScanEventTarget>,
+    attempts: Vec<ScanEventAttempt>,
+}
 
 #[doc(hidden)]
 #[get("/status/{id}")]
@@ -13,9 +24,22 @@ pub async fn status(
     manager: Data<PulseManager>,
     _auth: AuthenticatedUser,
 ) -> Result<impl Responder> {
-    match manager.get_event(&id) {
-        Ok(Some(event)) => Ok(HttpResponse::Ok().json(event)),
-        Ok(None) => Ok(HttpResponse::NotFound().body("Event not found")),
+    let event = match manager.get_event(&id) {
+        Ok(Some(event)) => event,
+        Ok(None) => return Ok(HttpResponse::NotFound().body("Event not found")),
+        Err(e) => return Ok(HttpResponse::InternalServerError().body(e.to_string())),
+    };
+
+    let history = manager
+        .get_event_targets(&event.id)
+        .and_then(|targets| Ok((targets, manager.get_event_attempts(&event.id)?)));
+
+    match history {
+        Ok((targets, attempts)) => Ok(HttpResponse::Ok().json(EventStatus {
+            event,
+            targets,
+            attempts,
+        })),
         Err(e) => Ok(HttpResponse::InternalServerError().body(e.to_string())),
     }
 }
@@ -0,0 +1,13 @@
+use crate::middleware::auth::AuthenticatedUser;
+use actix_web::web::Data;
+use actix_web::{get, HttpResponse};
+use actix_web::{Responder, Result};
+use autopulse_service::manager::PulseManager;
+
+#[get("/targets")]
+pub async fn targets(
+    manager: Data<PulseManager>,
+    _auth: AuthenticatedUser,
+) -> Result<impl Responder> {
+    Ok(HttpResponse::Ok().json(manager.get_target_statuses()))
+}
@@ -10,7 +10,7 @@ use autopulse_service::{
     manager::PulseManager, settings::triggers::manual::ManualQueryParams,
     settings::webhooks::EventType,
 };
-use autopulse_utils::sify;
+use autopulse_utils::{sify, FileHash};
 use serde::Deserialize;
 use tracing::{debug, debug_span, error, info};
 
@@ -45,7 +45,7 @@ pub async fn trigger_post(
     };
 
     match trigger_settings {
-        Trigger::Manual(_) | Trigger::Notify(_) => {
+        Trigger::Manual(_) | Trigger::Notify(_) | Trigger::Sweep(_) => {
             Ok(HttpResponse::BadRequest().body("Invalid request"))
         }
         _ => {
@@ -64,11 +64,13 @@ pub async fn trigger_post(
 
             let mut excluded_paths: Vec<String> = vec![];
 
-            for path in &paths {
-                let (mut path, search) = path.clone();
+            for event in &paths {
+                let mut path = event.path.clone();
+                let mut previous_path = event.previous_path.clone();
 
                 if let Some(rewrite) = &rewrite {
                     path = rewrite.rewrite_path(path);
+                    previous_path = previous_path.map(|p| rewrite.rewrite_path(p));
                 }
 
                 if !trigger_settings.should_process_path(&path) {
@@ -80,7 +82,7 @@ pub async fn trigger_post(
                 let new_scan_event = NewScanEvent {
                     event_source: trigger_name.clone(),
                     file_path: path.clone(),
-                    found_status: if !search {
+                    found_status: if !event.search() {
                         FoundStatus::Found.into()
                     } else {
                         FoundStatus::NotFound.into()
@@ -92,6 +94,8 @@ pub async fn trigger_post(
                                 .unwrap_or(manager.settings.opts.default_timer_wait)
                                 as i64,
                         ),
+                    event_kind: event.kind.into(),
+                    previous_path,
                     ..Default::default()
                 };
 
@@ -147,6 +151,10 @@ async fn trigger_get_inner(
                     return Ok(HttpResponse::NoContent().finish());
                 }
 
+                if let Some(Err(e)) = query.hash.as_deref().map(str::parse::<FileHash>) {
+                    return Ok(HttpResponse::BadRequest().body(e.to_string()));
+                }
+
                 let new_scan_event = NewScanEvent {
                     event_source: trigger_name.to_owned(),
                     file_path: file_path.clone(),
@@ -0,0 +1,85 @@
+use crate::routes::metrics::metrics;
+use actix_web::{
+    http::StatusCode,
+    test::{self, TestRequest},
+    web::Data,
+    App,
+};
+use actix_web_httpauth::extractors::basic;
+use autopulse_database::conn::{get_conn, get_pool};
+use autopulse_database::models::NewScanEvent;
+use autopulse_service::{manager::PulseManager, settings::Settings};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn test_manager(settings: Settings) -> PulseManager {
+    let unique_id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock should be after unix epoch")
+        .as_nanos();
+    let database_url = format!("sqlite:///tmp/autopulse-server-metrics-{unique_id}.db");
+
+    let mut settings = settings;
+    settings.app.database_url = database_url.clone();
+
+    let pool = get_pool(&database_url).expect("test database pool should initialize");
+    get_conn(&pool)
+        .expect("test database connection should initialize")
+        .migrate()
+        .expect("test database migrations should apply");
+
+    PulseManager::new(settings, pool)
+}
+
+#[actix_web::test]
+async fn metrics_endpoint_requires_auth_by_default() {
+    let manager = test_manager(Settings::default());
+    let app = test::init_service(
+        App::new()
+            .service(metrics)
+            .app_data(basic::Config::default().realm("Restricted area"))
+            .app_data(Data::new(manager)),
+    )
+    .await;
+
+    let response = test::call_service(&app, TestRequest::get().uri("/metrics").to_request()).await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn metrics_endpoint_can_be_public_and_counts_ingested_events() {
+    let mut settings = Settings::default();
+    settings.auth.public_metrics = true;
+
+    let manager = test_manager(settings);
+    manager
+        .add_event(&NewScanEvent {
+            event_source: "my_sonarr".to_string(),
+            ..Default::default()
+        })
+        .expect("test scan event should insert");
+
+    let app = test::init_service(
+        App::new()
+            .service(metrics)
+            .app_data(basic::Config::default().realm("Restricted area"))
+            .app_data(Data::new(manager)),
+    )
+    .await;
+
+    let response = test::call_service(&app, TestRequest::get().uri("/metrics").to_request()).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec())
+        .expect("metrics should be utf-8");
+
+    assert!(
+        body.contains(r#"autopulse_events_ingested_total{trigger="my_sonarr"} 1"#),
+        "unexpected metrics: {body}"
+    );
+    assert!(
+        body.contains("autopulse_anchors_available 1"),
+        "unexpected metrics: {body}"
+    );
+}
@@ -0,0 +1,92 @@
+use crate::routes::targets::targets;
+use actix_web::{
+    test::{self, TestRequest},
+    web::Data,
+    App,
+};
+use actix_web_httpauth::extractors::basic;
+use autopulse_database::conn::{get_conn, get_pool};
+use autopulse_service::manager::PulseManager;
+use autopulse_service::settings::Settings;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn test_manager() -> PulseManager {
+    let unique_id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock should be after unix epoch")
+        .as_nanos();
+    let database_url = format!("sqlite:///tmp/autopulse-server-targets-{unique_id}.db");
+
+    let mut settings = Settings::default();
+    settings.app.database_url = database_url.clone();
+    settings.opts.circuit_threshold = 1;
+
+    for (name, url) in [
+        ("b_plex", "http://plex:32400"),
+        ("a_jellyfin", "http://jellyfin:8096"),
+    ] {
+        let kind = name.split_once('_').expect("name has a type").1;
+        settings.targets.insert(
+            name.to_string(),
+            serde_json::from_value(serde_json::json!({
+                "type": kind,
+                "url": url,
+                "token": "token"
+            }))
+            .expect("target JSON should deserialize"),
+        );
+    }
+
+    let pool = get_pool(&database_url).expect("test database pool should initialize");
+    get_conn(&pool)
+        .expect("test database connection should initialize")
+        .migrate()
+        .expect("test database migrations should apply");
+
+    PulseManager::new(settings, pool)
+}
+
+#[actix_web::test]
+async fn targets_endpoint_reports_circuit_state_sorted_by_name() {
+    let manager = test_manager();
+    manager.circuits.record(
+        "b_plex",
+        chrono::Utc::now().naive_utc(),
+        Err("connection refused".to_string()),
+    );
+
+    let app = test::init_service(
+        App::new()
+            .service(targets)
+            .app_data(basic::Config::default().realm("Restricted area"))
+            .app_data(Data::new(manager)),
+    )
+    .await;
+
+    let response = test::call_service(
+        &app,
+        TestRequest::get()
+            .uri("/targets")
+            .insert_header(("Authorization", Settings::default().auth.to_auth_encoded()))
+            .to_request(),
+    )
+    .await;
+
+    assert!(
+        response.status().is_success(),
+        "status={}",
+        response.status()
+    );
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    let statuses = body.as_array().expect("response should be an array");
+
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0]["name"], "a_jellyfin");
+    assert_eq!(statuses[0]["type"], "jellyfin");
+    assert_eq!(statuses[0]["state"], "closed");
+    assert_eq!(statuses[1]["name"], "b_plex");
+    assert_eq!(statuses[1]["state"], "open");
+    assert_eq!(statuses[1]["consecutive_failures"], 1);
+    assert_eq!(statuses[1]["last_error"], "connection refused");
+}
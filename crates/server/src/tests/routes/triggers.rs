@@ -54,6 +54,12 @@ fn test_manager() -> PulseManager {
         .expect("sportarr trigger JSON should deserialize"),
     );
 
+    settings.triggers.insert(
+        "manual".to_string(),
+        serde_json::from_value(serde_json::json!({ "type": "manual" }))
+            .expect("manual trigger JSON should deserialize"),
+    );
+
     let pool = get_pool(&database_url).expect("test database pool should initialize");
     get_conn(&pool)
         .expect("test database connection should initialize")
@@ -144,6 +150,44 @@ async fn autoscan_trigger_applies_rewrite_to_dir() {
     assert_eq!(path, "/media/show/episode.mkv", "rewrite must be applied");
 }
 
+#[actix_web::test]
+async fn manual_trigger_accepts_prefixed_hashes_and_rejects_unknown_algorithms() {
+    let manager = test_manager();
+    let app = test::init_service(
+        App::new()
+            .service(trigger_get)
+            .app_data(basic::Config::default().realm("Restricted area"))
+            .app_data(Data::new(manager)),
+    )
+    .await;
+
+    let request = |hash: &str| {
+        TestRequest::get()
+            .uri(&format!(
+                "/triggers/manual?path=/media/movie.mkv&hash={hash}"
+            ))
+            .insert_header(("Authorization", test_auth_header()))
+            .to_request()
+    };
+
+    let response = test::call_service(&app, request("xxh3:9a4f3b2c1d0e8f76")).await;
+    assert!(
+        response.status().is_success(),
+        "status={}",
+        response.status()
+    );
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["file_hash"], "xxh3:9a4f3b2c1d0e8f76");
+
+    let response = test::call_service(&app, request("crc32:deadbeef")).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let body = test::read_body(response).await;
+    assert!(
+        String::from_utf8_lossy(&body).contains("unknown hash algorithm 'crc32'"),
+        "{body:?}"
+    );
+}
+
 #[actix_web::test]
 async fn sportarr_trigger_parses_download_webhook() {
     let manager = test_manager();
@@ -4,11 +4,12 @@ use actix_web::{
     web::{Data, Form, Query},
     HttpResponse, Result,
 };
-use autopulse_database::models::NewScanEvent;
+use autopulse_database::models::{EventKind, NewScanEvent};
 use autopulse_service::{
     manager::PulseManager,
     settings::{rewrite::Rewrite, webhooks::EventType},
 };
+use autopulse_utils::FileHash;
 use maud::{html, Markup};
 use serde::Deserialize;
 
@@ -131,7 +132,7 @@ fn render_form(
                     label.form__field {
                         span.form__label { "Hash" span.form__optional { "optional" } }
                         input type="text" name="hash"
-                            placeholder="sha256 — leave blank to skip verification"
+                            placeholder="sha256, or prefixed like xxh3:… — leave blank to skip verification"
                             value=(q.hash.as_deref().unwrap_or(""));
                         span.form__hint { "If set, autopulse waits until the file on disk matches this checksum before scanning." }
                     }
@@ -164,7 +165,12 @@ fn preview_path(resolved: &Resolved, path: &str) -> (String, bool) {
     (path.to_string(), false)
 }
 
-fn preview_fragment(resolved: &Resolved, path: &str, exists: bool) -> Markup {
+fn preview_fragment(
+    resolved: &Resolved,
+    path: &str,
+    exists: bool,
+    targets: &[(String, String)],
+) -> Markup {
     let path = path.trim();
 
     let (rewritten, had_rewrite) = preview_path(resolved, path);
@@ -195,6 +201,18 @@ fn preview_fragment(resolved: &Resolved, path: &str, exists: bool) -> Markup {
             @if exists { "✓ Found on disk — autopulse can see this file" }
             @else { "✗ Not found on disk at this path on the autopulse host" }
         }
+        .preview__targets {
+            span.preview__tag { "targets" }
+            @if targets.is_empty() {
+                p.preview__note { "No target would receive this event." }
+            }
+            @for (name, target_path) in targets {
+                .preview__target {
+                    span.preview__target-name { (name) }
+                    code.preview__path.preview__path--to { (target_path) }
+                }
+            }
+        }
     }
 }
 
@@ -212,11 +230,18 @@ async fn preview(manager: &PulseManager, q: &AddQuery) -> Markup {
 
     let resolved = resolve_trigger(manager, q.trigger.as_deref());
     let (rewritten, _) = preview_path(&resolved, trimmed);
+    // Routes, excludes and filters as the runner applies them to a manual scan
+    let targets = manager
+        .settings
+        .route(&resolved.name, EventKind::default(), &rewritten)
+        .into_iter()
+        .map(|(name, routed)| (name.clone(), routed.path))
+        .collect::<Vec<_>>();
     let exists = actix_web::web::block(move || std::path::Path::new(&rewritten).exists())
         .await
         .unwrap_or(false);
 
-    preview_fragment(&resolved, trimmed, exists)
+    preview_fragment(&resolved, trimmed, exists, &targets)
 }
 
 #[get("/ui/add/preview")]
@@ -273,6 +298,9 @@ pub async fn add_post(
         .as_ref()
         .map(|h| h.trim().to_string())
         .filter(|h| !h.is_empty());
+    if let Some(Err(e)) = hash.as_deref().map(str::parse::<FileHash>) {
+        return Err(ErrorBadRequest(e.to_string()));
+    }
 
     let new_scan_event = NewScanEvent {
         event_source: resolved.name.clone(),
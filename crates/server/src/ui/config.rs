@@ -121,6 +121,7 @@ fn doc_for(path: &[&str]) -> Option<String> {
         ["triggers"] | ["triggers", _] => format!("{DOCS}/triggers/enum.Trigger"),
         ["targets"] | ["targets", _] => format!("{DOCS}/targets/enum.Target"),
         ["webhooks"] | ["webhooks", _] => format!("{DOCS}/webhooks/enum.Webhook"),
+        ["routes"] | ["routes", _] => format!("{DOCS}/routes/struct.Route"),
         ["anchors"] => format!("{DOCS}/struct.Settings#structfield.anchors"),
         // sub-options: flat sections have a doc page per struct, with a
         // `#structfield.<name>` anchor for each field. (triggers/targets/
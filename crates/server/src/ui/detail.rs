@@ -4,7 +4,7 @@ use actix_web::{
     web::{Data, Path},
     Result,
 };
-use autopulse_database::models::{ProcessStatus, ScanEvent};
+use autopulse_database::models::{ProcessStatus, ScanEvent, ScanEventAttempt, ScanEventTarget};
 use autopulse_service::manager::PulseManager;
 use chrono::NaiveDateTime;
 use maud::{html, Markup, PreEscaped};
@@ -30,11 +30,12 @@ pub async fn event_detail(
     let status: ProcessStatus = ev.process_status.parse().unwrap_or(ProcessStatus::Pending);
     let status_str: &'static str = status.into();
 
-    let targets = ev
-        .targets_hit
-        .split(',')
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>();
+    let targets = manager
+        .get_event_targets(&ev.id)
+        .map_err(ErrorInternalServerError)?;
+    let attempts = manager
+        .get_event_attempts(&ev.id)
+        .map_err(ErrorInternalServerError)?;
 
     let ctx_ = ctx(&manager, &csrf.0);
     let base = ctx_.base;
@@ -121,6 +122,10 @@ pub async fn event_detail(
                                 }
                             }))
                             (kv("Source", html! { (ev.event_source) }))
+                            (kv("Kind", html! { (ev.event_kind) }))
+                            @if let Some(previous) = &ev.previous_path {
+                                (kv("Previous path", html! { code.mono { (previous) } }))
+                            }
                             (kv("Found status", html! {
                                 span.detail__found .{ "detail__found--" (found_class(&ev.found_status)) } {
                                     (ev.found_status.replace('_', " "))
@@ -133,15 +138,6 @@ pub async fn event_detail(
                                 }
                             }))
                             (kv("Failed times", html! { (ev.failed_times) }))
-                            (kv("Targets hit", html! {
-                                @if targets.is_empty() {
-                                    span.dim { "\u{2014}" }
-                                } @else {
-                                    ul.tags {
-                                        @for t in &targets { li.tag { (t) } }
-                                    }
-                                }
-                            }))
                         }
                     }
 
@@ -157,6 +153,40 @@ pub async fn event_detail(
                             (kv_ts("Next retry at", &ev.next_retry_at))
                         }
                     }
+
+                    div.detail__card.detail__card--wide {
+                        h3.detail__card-head { "Targets" }
+                        @if targets.is_empty() {
+                            p.detail__empty.dim { "No targets attempted yet" }
+                        } @else {
+                            ul.detail__targets {
+                                @for t in &targets { (target_row(t)) }
+                            }
+                        }
+                    }
+
+                    div.detail__card.detail__card--wide {
+                        h3.detail__card-head { "Attempts" }
+                        @if attempts.is_empty() {
+                            p.detail__empty.dim { "No attempts yet" }
+                        } @else {
+                            div.detail__attempts-wrap {
+                                table.detail__attempts {
+                                    thead {
+                                        tr {
+                                            th { "Time" }
+                                            th { "Target" }
+                                            th { "Result" }
+                                            th { "Duration" }
+                                        }
+                                    }
+                                    tbody {
+                                        @for a in &attempts { (attempt_row(a)) }
+                                    }
+                                }
+                            }
+                        }
+                    }
                 }
 
                 @if matches!(status, ProcessStatus::Failed | ProcessStatus::Retry | ProcessStatus::Complete) {
@@ -249,6 +279,61 @@ fn pipeline_step(label: &str, state: &str) -> Markup {
     }
 }
 
+fn target_row(t: &ScanEventTarget) -> Markup {
+    html! {
+        li.detail__target {
+            div.detail__target-head {
+                span.detail__target-name { (t.target) }
+                span.badge .{ "badge--" (t.status) } { (t.status) }
+            }
+            div.detail__target-meta.dim {
+                (t.attempts)
+                @if t.attempts == 1 { " attempt" } @else { " attempts" }
+                @if let Some(at) = t.next_retry_at {
+                    " \u{00b7} next retry "
+                    time.detail__ts datetime=(at.format("%Y-%m-%dT%H:%M:%SZ")) {
+                        code.mono { (at.format("%Y-%m-%d %H:%M:%S")) }
+                        " "
+                        span.detail__ts-rel {}
+                    }
+                }
+            }
+            @if let Some(err) = &t.last_error {
+                code.detail__target-error { (err) }
+            }
+        }
+    }
+}
+
+fn attempt_row(a: &ScanEventAttempt) -> Markup {
+    html! {
+        tr.detail__attempt .is-error[!a.success] {
+            td {
+                time.detail__ts datetime=(a.attempted_at.format("%Y-%m-%dT%H:%M:%SZ")) {
+                    code.mono { (a.attempted_at.format("%Y-%m-%d %H:%M:%S")) }
+                }
+            }
+            td { (a.target) }
+            td {
+                @if a.success {
+                    span.badge.badge--complete { "ok" }
+                } @else {
+                    span.badge.badge--failed {
+                        @match a.http_status {
+                            Some(status) => (status),
+                            None => "error",
+                        }
+                    }
+                    @if let Some(err) = &a.error {
+                        code.detail__target-error { (err) }
+                    }
+                }
+            }
+            td.mono { (a.duration_ms) " ms" }
+        }
+    }
+}
+
 fn kv(label: &str, value: Markup) -> Markup {
     html! {
         div.detail__kv {
@@ -281,6 +366,7 @@ fn found_class(status: &str) -> &'static str {
         "found" => "found",
         "not_found" => "not_found",
         "hash_mismatch" => "hash_mismatch",
+        "unstable" => "unstable",
         _ => "unknown",
     }
 }
@@ -148,6 +148,11 @@ fn stats_cards(
     status: Option<&str>,
     search: Option<&str>,
 ) -> Markup {
+    let total_sub = match stats.expired {
+        0 => "Total scan events".to_string(),
+        expired => format!("Total scan events, {expired} expired unprocessed"),
+    };
+
     let cards: [(_, i64, _, icondata::Icon, Option<&str>); 5] = [
         (
             "Pending",
@@ -180,7 +185,7 @@ fn stats_cards(
         (
             "Total",
             stats.total,
-            "Total scan events",
+            total_sub.as_str(),
             icondata::LuCopy,
             None,
         ),
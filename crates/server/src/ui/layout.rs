@@ -41,9 +41,10 @@ pub fn page(ctx: &Ctx<'_>, title: &str, nav: &str, content: Markup) -> Markup {
                             }
                         }
                         nav.rail__nav {
-                            (rail_link(base, "/ui/events", "Events",   icondata::LuList,            nav == "events"))
-                            (rail_link(base, "/ui/add",    "Add scan", icondata::LuPlus,            nav == "add"))
-                            (rail_link(base, "/ui/config", "Config",   icondata::LuSlidersHorizontal, nav == "config"))
+                            (rail_link(base, "/ui/events",  "Events",   icondata::LuList,              nav == "events"))
+                            (rail_link(base, "/ui/add",     "Add scan", icondata::LuPlus,              nav == "add"))
+                            (rail_link(base, "/ui/targets", "Targets",  icondata::LuActivity,          nav == "targets"))
+                            (rail_link(base, "/ui/config",  "Config",   icondata::LuSlidersHorizontal, nav == "config"))
                         }
                         .rail__spacer {}
                         .rail__foot {
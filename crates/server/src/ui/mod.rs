@@ -11,6 +11,7 @@ pub mod layout;
 pub mod session_key;
 pub mod static_assets;
 pub mod stream;
+pub mod targets;
 
 use actix_web::{
     get,
@@ -56,5 +57,6 @@ pub fn configure(cfg: &mut ServiceConfig) {
         .service(add::add_preview)
         .service(add::add_page)
         .service(add::add_post)
-        .service(config::config_page);
+        .service(config::config_page)
+        .service(targets::targets_page);
 }
@@ -0,0 +1,72 @@
+use actix_web::{get, web::Data, Result};
+use autopulse_service::manager::PulseManager;
+use chrono::NaiveDateTime;
+use maud::{html, Markup};
+
+use crate::ui::{
+    auth::{ctx, SessionUser},
+    csrf::CsrfToken,
+    layout,
+};
+
+/// Circuit breaker health of every configured target.
+#[get("/ui/targets")]
+pub async fn targets_page(
+    manager: Data<PulseManager>,
+    _user: SessionUser,
+    csrf: CsrfToken,
+) -> Result<Markup> {
+    let statuses = manager.get_target_statuses();
+
+    let ctx_ = ctx(&manager, &csrf.0);
+    let body = html! {
+        section.targets {
+            header.page-head {
+                h1.page-title { "Targets" }
+                span.page-meta { "circuit breakers reset on restart" }
+            }
+
+            .events__table-wrap {
+              .events__table-scroll {
+                table.events__table {
+                    thead { tr {
+                        th { "Name" } th { "Type" } th { "State" }
+                        th { "Failures" } th { "Retry at" } th { "Last error" }
+                    } }
+                    tbody {
+                        @if statuses.is_empty() {
+                            tr { td.dim colspan="6" { "No targets configured" } }
+                        }
+                        @for status in &statuses {
+                            @let state = serde_json::to_value(status.health.state)
+                                .ok()
+                                .and_then(|v| v.as_str().map(String::from))
+                                .unwrap_or_default();
+                            tr {
+                                td { (status.name) }
+                                td.dim { (status.kind) }
+                                td { span.badge .{ "badge--" (state) } { (state.replace('_', " ")) } }
+                                td data-num=(status.health.consecutive_failures) {
+                                    (status.health.consecutive_failures)
+                                }
+                                td { (timestamp(status.health.retry_at)) }
+                                td.dim { (status.health.last_error.as_deref().unwrap_or("")) }
+                            }
+                        }
+                    }
+                }
+              }
+            }
+        }
+    };
+
+    Ok(layout::page(&ctx_, "targets", "targets", body))
+}
+
+fn timestamp(at: Option<NaiveDateTime>) -> Markup {
+    html! {
+        @if let Some(at) = at {
+            time.local-ts datetime=(at.format("%Y-%m-%dT%H:%M:%SZ")) { (at) }
+        }
+    }
+}
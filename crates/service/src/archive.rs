@@ -0,0 +1,41 @@
+use anyhow::Context;
+use autopulse_database::models::ScanEvent;
+use chrono::NaiveDate;
+use flate2::{write::GzEncoder, Compression};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The archive file events pruned on `day` are appended to.
+pub fn archive_path(dir: &Path, day: NaiveDate) -> PathBuf {
+    dir.join(format!("scan_events-{}.ndjson.gz", day.format("%Y-%m-%d")))
+}
+
+/// Appends `events` to the archive file of `day` in `dir`, one JSON object per line.
+///
+/// Each call adds a gzip member to the end of the file, which `zcat` and other gzip
+/// readers decompress as one stream. The file is synced before returning so events are
+/// only deleted once they are on disk.
+pub fn append(dir: &Path, day: NaiveDate, events: &[ScanEvent]) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create archive directory {}", dir.display()))?;
+
+    let path = archive_path(dir, day);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open archive {}", path.display()))?;
+
+    let mut encoder = GzEncoder::new(file, Compression::default());
+
+    for ev in events {
+        serde_json::to_writer(&mut encoder, ev)?;
+        encoder.write_all(b"\n")?;
+    }
+
+    encoder
+        .finish()
+        .and_then(|file| file.sync_all())
+        .with_context(|| format!("failed to write archive {}", path.display()))
+}
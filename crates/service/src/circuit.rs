@@ -0,0 +1,198 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// State of a target's circuit breaker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Events are dispatched as usual.
+    #[default]
+    Closed,
+    /// Dispatch is paused until `retry_at`.
+    Open,
+    /// A single event is dispatched to probe whether the target recovered.
+    HalfOpen,
+}
+
+/// Health of a single target, as shown by `/targets` and the UI.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TargetHealth {
+    pub state: CircuitState,
+    /// Dispatches in a row where no event succeeded.
+    pub consecutive_failures: u32,
+    /// When an open circuit will let a probe through.
+    pub retry_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<NaiveDateTime>,
+    pub last_failure_at: Option<NaiveDateTime>,
+}
+
+/// What the runner may send to a target this tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Admission {
+    /// Every due event.
+    All,
+    /// A single event, to probe a half-open circuit.
+    Probe,
+    /// Nothing until the given time.
+    Paused(NaiveDateTime),
+}
+
+/// A circuit opening or closing, sent out through the webhooks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    Opened,
+    Closed,
+}
+
+/// Per-target circuit breakers, opened after `threshold` consecutive failed
+/// dispatches and probed again after `cooldown` seconds.
+///
+/// State is kept in memory, so every circuit starts closed after a restart.
+pub struct CircuitBreakers {
+    threshold: u32,
+    cooldown: chrono::Duration,
+    health: Mutex<HashMap<String, TargetHealth>>,
+}
+
+impl CircuitBreakers {
+    /// A `threshold` of 0 disables the breakers.
+    pub fn new(threshold: u32, cooldown_secs: u64) -> Self {
+        Self {
+            threshold,
+            cooldown: chrono::Duration::seconds(cooldown_secs as i64),
+            health: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn admit(&self, target: &str, now: NaiveDateTime) -> Admission {
+        if self.threshold == 0 {
+            return Admission::All;
+        }
+
+        let mut health = self.health.lock().expect("circuit lock poisoned");
+        let Some(health) = health.get_mut(target) else {
+            return Admission::All;
+        };
+
+        match (health.state, health.retry_at) {
+            (CircuitState::Closed, _) => Admission::All,
+            (CircuitState::Open, Some(retry_at)) if now < retry_at => Admission::Paused(retry_at),
+            (CircuitState::Open | CircuitState::HalfOpen, _) => {
+                health.state = CircuitState::HalfOpen;
+                Admission::Probe
+            }
+        }
+    }
+
+    /// Records the outcome of a dispatch, `Err` when no event succeeded.
+    pub fn record(
+        &self,
+        target: &str,
+        now: NaiveDateTime,
+        outcome: Result<(), String>,
+    ) -> Option<Transition> {
+        let mut health = self.health.lock().expect("circuit lock poisoned");
+        let health = health.entry(target.to_string()).or_default();
+
+        match outcome {
+            Ok(()) => {
+                health.consecutive_failures = 0;
+                health.last_success_at = Some(now);
+                health.retry_at = None;
+
+                let was_open = health.state != CircuitState::Closed;
+                health.state = CircuitState::Closed;
+
+                was_open.then_some(Transition::Closed)
+            }
+            Err(e) => {
+                health.consecutive_failures += 1;
+                health.last_failure_at = Some(now);
+                health.last_error = Some(e);
+
+                let trips = match health.state {
+                    CircuitState::HalfOpen => true,
+                    CircuitState::Closed => {
+                        self.threshold > 0 && health.consecutive_failures >= self.threshold
+                    }
+                    CircuitState::Open => false,
+                };
+
+                if !trips {
+                    return None;
+                }
+
+                let reopened = health.state == CircuitState::HalfOpen;
+                health.state = CircuitState::Open;
+                health.retry_at = Some(now + self.cooldown);
+
+                // A failed probe keeps the circuit open, it does not open it again
+                (!reopened).then_some(Transition::Opened)
+            }
+        }
+    }
+
+    pub fn health(&self, target: &str) -> TargetHealth {
+        self.health
+            .lock()
+            .expect("circuit lock poisoned")
+            .get(target)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    #[test]
+    fn opens_after_threshold_and_closes_after_a_successful_probe() {
+        let circuits = CircuitBreakers::new(2, 60);
+
+        assert_eq!(circuits.record("plex", at(0), Err("down".into())), None);
+        assert_eq!(circuits.admit("plex", at(1)), Admission::All);
+        assert_eq!(
+            circuits.record("plex", at(1), Err("down".into())),
+            Some(Transition::Opened)
+        );
+        assert_eq!(circuits.admit("plex", at(30)), Admission::Paused(at(61)));
+        assert_eq!(circuits.admit("plex", at(61)), Admission::Probe);
+        assert_eq!(circuits.health("plex").state, CircuitState::HalfOpen);
+        assert_eq!(
+            circuits.record("plex", at(62), Ok(())),
+            Some(Transition::Closed)
+        );
+        assert_eq!(circuits.admit("plex", at(63)), Admission::All);
+        assert_eq!(circuits.health("plex").consecutive_failures, 0);
+    }
+
+    #[test]
+    fn failed_probe_reopens_without_a_new_transition() {
+        let circuits = CircuitBreakers::new(1, 60);
+
+        circuits.record("plex", at(0), Err("down".into()));
+        assert_eq!(circuits.admit("plex", at(60)), Admission::Probe);
+        assert_eq!(circuits.record("plex", at(60), Err("down".into())), None);
+        assert_eq!(circuits.admit("plex", at(61)), Admission::Paused(at(120)));
+    }
+
+    #[test]
+    fn zero_threshold_never_opens() {
+        let circuits = CircuitBreakers::new(0, 60);
+
+        for i in 0..10 {
+            assert_eq!(circuits.record("plex", at(i), Err("down".into())), None);
+        }
+        assert_eq!(circuits.admit("plex", at(10)), Admission::All);
+    }
+}
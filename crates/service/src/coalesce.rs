@@ -0,0 +1,187 @@
+use autopulse_database::models::{EventKind, ScanEvent};
+use autopulse_utils::RuntimePath;
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+
+/// The events a coalescing target gets this tick.
+pub struct Coalesced<'a> {
+    /// Events to send, a directory event stands in for the events it merged
+    pub send: Vec<ScanEvent>,
+    /// The original events behind `send`
+    pub members: Vec<&'a ScanEvent>,
+    /// Id of each directory event, with the ids of the other events it stands in for
+    pub merged: Vec<(String, Vec<String>)>,
+    /// Events waiting for their directory to settle, and until when
+    pub waiting: Vec<(&'a ScanEvent, NaiveDateTime)>,
+}
+
+/// Collapses events that share a directory into one event for that directory.
+///
+/// A directory is only sent once `window` seconds have passed since its newest event
+/// became due, so the rest of a burst can join it. `dir` gives the directory of an
+/// event as the target sees it. Renames keep their own event so `previous_path` survives.
+pub fn coalesce<'a>(
+    evs: Vec<&'a ScanEvent>,
+    window: u64,
+    now: NaiveDateTime,
+    dir: impl Fn(&ScanEvent) -> String,
+) -> Coalesced<'a> {
+    let mut coalesced = Coalesced {
+        send: vec![],
+        members: vec![],
+        merged: vec![],
+        waiting: vec![],
+    };
+
+    let mut groups: BTreeMap<(String, String), Vec<&ScanEvent>> = BTreeMap::new();
+
+    for ev in evs {
+        if ev.kind() == EventKind::Renamed {
+            groups
+                .entry((ev.id.clone(), String::new()))
+                .or_default()
+                .push(ev);
+        } else {
+            groups
+                .entry((dir(ev), ev.event_kind.clone()))
+                .or_default()
+                .push(ev);
+        }
+    }
+
+    for group in groups.into_values() {
+        let settles_at = group
+            .iter()
+            .map(|ev| ev.can_process)
+            .max()
+            .expect("groups are never empty")
+            + chrono::Duration::seconds(window as i64);
+
+        if settles_at > now {
+            coalesced
+                .waiting
+                .extend(group.into_iter().map(|ev| (ev, settles_at)));
+            continue;
+        }
+
+        if let [ev] = group.as_slice() {
+            coalesced.send.push((*ev).clone());
+            coalesced.members.push(ev);
+            continue;
+        }
+
+        let mut directory = group[0].clone();
+        directory.file_path = RuntimePath::new(&group[0].file_path)
+            .parent_or_self()
+            .as_str()
+            .to_string();
+        directory.file_hash = None;
+        directory.previous_path = None;
+
+        coalesced.merged.push((
+            directory.id.clone(),
+            group[1..].iter().map(|ev| ev.id.clone()).collect(),
+        ));
+        coalesced.send.push(directory);
+        coalesced.members.extend(group);
+    }
+
+    coalesced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use autopulse_database::models::{FoundStatus, ProcessStatus};
+
+    fn at(secs: i64) -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    fn event(id: &str, path: &str, kind: EventKind, due: i64) -> ScanEvent {
+        ScanEvent {
+            id: id.to_string(),
+            event_source: "trigger".to_string(),
+            event_timestamp: at(due),
+            file_path: path.to_string(),
+            file_hash: None,
+            process_status: ProcessStatus::Pending.into(),
+            found_status: FoundStatus::Found.into(),
+            failed_times: 0,
+            next_retry_at: None,
+            found_at: Some(at(due)),
+            processed_at: None,
+            created_at: at(due),
+            updated_at: at(due),
+            can_process: at(due),
+            event_kind: kind.into(),
+            previous_path: None,
+            claimed_by: None,
+            claimed_until: None,
+        }
+    }
+
+    fn parent(ev: &ScanEvent) -> String {
+        RuntimePath::new(&ev.file_path)
+            .parent_or_self()
+            .as_str()
+            .to_string()
+    }
+
+    #[test]
+    fn collapses_a_settled_directory_into_one_event() {
+        let a = event("a", "/tv/Show/Season 1/e01.mkv", EventKind::Created, 0);
+        let b = event("b", "/tv/Show/Season 1/e02.mkv", EventKind::Created, 5);
+        let c = event("c", "/tv/Other/e01.mkv", EventKind::Created, 0);
+
+        let coalesced = coalesce(vec![&a, &b, &c], 10, at(15), parent);
+
+        assert!(coalesced.waiting.is_empty());
+        assert_eq!(coalesced.members.len(), 3);
+        assert_eq!(
+            coalesced
+                .send
+                .iter()
+                .map(|ev| ev.file_path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/tv/Other/e01.mkv", "/tv/Show/Season 1"]
+        );
+        assert_eq!(
+            coalesced.merged,
+            vec![("a".to_string(), vec!["b".to_string()])]
+        );
+    }
+
+    #[test]
+    fn waits_for_the_newest_event_of_a_directory() {
+        let a = event("a", "/tv/Show/Season 1/e01.mkv", EventKind::Created, 0);
+        let b = event("b", "/tv/Show/Season 1/e02.mkv", EventKind::Created, 8);
+
+        let coalesced = coalesce(vec![&a, &b], 10, at(12), parent);
+
+        assert!(coalesced.send.is_empty());
+        assert_eq!(
+            coalesced
+                .waiting
+                .iter()
+                .map(|(ev, until)| (ev.id.as_str(), *until))
+                .collect::<Vec<_>>(),
+            vec![("a", at(18)), ("b", at(18))]
+        );
+    }
+
+    #[test]
+    fn keeps_renames_and_other_kinds_apart() {
+        let a = event("a", "/tv/Show/e01.mkv", EventKind::Created, 0);
+        let b = event("b", "/tv/Show/e02.mkv", EventKind::Deleted, 0);
+        let c = event("c", "/tv/Show/e03.mkv", EventKind::Renamed, 0);
+        let d = event("d", "/tv/Show/e04.mkv", EventKind::Renamed, 0);
+
+        let coalesced = coalesce(vec![&a, &b, &c, &d], 0, at(0), parent);
+
+        assert_eq!(coalesced.send.len(), 4);
+        assert!(coalesced.merged.is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::stability::FileState;
+use autopulse_utils::{checksum, FileHash, HashAlgorithm};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::sync::Semaphore;
+
+struct CachedDigest {
+    state: FileState,
+    digest: String,
+}
+
+/// Hashes files for `file_hash` checks.
+///
+/// Digests are cached by path and algorithm and reused while the file's size and
+/// modified time are unchanged, and at most `concurrency` files are hashed at once.
+pub struct FileHasher {
+    permits: Semaphore,
+    cache: Mutex<HashMap<(PathBuf, HashAlgorithm), CachedDigest>>,
+}
+
+impl FileHasher {
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            permits: Semaphore::new(concurrency.max(1)),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lowercase hex digest of the file, from the cache when it has not changed.
+    pub async fn digest(&self, path: &Path, algorithm: HashAlgorithm) -> anyhow::Result<String> {
+        let key = (path.to_path_buf(), algorithm);
+        let state = FileState::read(path)?;
+
+        if let Some(cached) = self.cache.lock().expect("hash cache poisoned").get(&key) {
+            if cached.state == state {
+                return Ok(cached.digest.clone());
+            }
+        }
+
+        let _permit = self.permits.acquire().await?;
+        let path = path.to_path_buf();
+        let digest = tokio::task::spawn_blocking(move || checksum(&path, algorithm))
+            .await
+            .map_err(|e| anyhow::anyhow!("hash task failed: {e}"))??;
+
+        self.cache.lock().expect("hash cache poisoned").insert(
+            key,
+            CachedDigest {
+                state,
+                digest: digest.clone(),
+            },
+        );
+
+        Ok(digest)
+    }
+
+    pub async fn matches(&self, path: &Path, expected: &FileHash) -> anyhow::Result<bool> {
+        Ok(self.digest(path, expected.algorithm).await? == expected.digest)
+    }
+
+    /// Drops the cached digests of a file that no longer needs checking.
+    pub fn forget(&self, path: &Path) {
+        self.cache
+            .lock()
+            .expect("hash cache poisoned")
+            .retain(|(cached, _), _| cached != path);
+    }
+
+    /// Keeps only the cached digests of files `keep` accepts.
+    pub fn retain(&self, mut keep: impl FnMut(&Path) -> bool) {
+        self.cache
+            .lock()
+            .expect("hash cache poisoned")
+            .retain(|(cached, _), _| keep(cached));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use autopulse_utils::generate_uuid;
+
+    #[tokio::test]
+    async fn unchanged_file_is_not_rehashed() {
+        let hasher = FileHasher::new(1);
+        let path = std::env::temp_dir().join(generate_uuid());
+        std::fs::write(&path, b"hi!").unwrap();
+
+        let expected: FileHash =
+            "sha256:c0ddd62c7717180e7ffb8a15bb9674d3ec92592e0b7ac7d1d5289836b4553be2"
+                .parse()
+                .unwrap();
+        assert!(hasher.matches(&path, &expected).await.unwrap());
+
+        // Swap the cached digest out to prove the file is not read again
+        hasher
+            .cache
+            .lock()
+            .unwrap()
+            .get_mut(&(path.clone(), HashAlgorithm::Sha256))
+            .unwrap()
+            .digest = "cached".to_string();
+        assert_eq!(
+            hasher.digest(&path, HashAlgorithm::Sha256).await.unwrap(),
+            "cached"
+        );
+
+        std::fs::write(&path, b"changed").unwrap();
+        assert_ne!(
+            hasher.digest(&path, HashAlgorithm::Sha256).await.unwrap(),
+            "cached",
+            "a size change invalidates the cache"
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn retain_drops_files_no_longer_checked() {
+        let hasher = FileHasher::new(1);
+        let kept = std::env::temp_dir().join(generate_uuid());
+        let dropped = std::env::temp_dir().join(generate_uuid());
+
+        for path in [&kept, &dropped] {
+            std::fs::write(path, b"hi!").unwrap();
+            hasher.digest(path, HashAlgorithm::Sha256).await.unwrap();
+        }
+
+        hasher.retain(|path| path == kept);
+
+        let cache = hasher.cache.lock().unwrap();
+        assert!(cache.contains_key(&(kept.clone(), HashAlgorithm::Sha256)));
+        assert!(!cache.contains_key(&(dropped.clone(), HashAlgorithm::Sha256)));
+        drop(cache);
+
+        std::fs::remove_file(&kept).unwrap();
+        std::fs::remove_file(&dropped).unwrap();
+    }
+}
@@ -1,7 +1,19 @@
+/// Gzip-compressed NDJSON archive of pruned events
+pub mod archive;
+/// Per-target circuit breakers
+pub mod circuit;
+/// Directory coalescing for bursts of events
+pub mod coalesce;
+/// Cached, rate limited file hashing
+pub mod hasher;
 #[doc(hidden)]
 pub mod manager;
+/// Prometheus metrics
+pub mod metrics;
 #[doc(hidden)]
 pub mod runner;
+/// File stability checks before events count as found
+pub mod stability;
 
 /// Settings for the service
 pub mod settings;
@@ -9,6 +21,21 @@ pub mod settings;
 #[cfg(test)]
 mod tests {
     mod manager_add_event;
+    mod manager_bus;
+    #[cfg(feature = "sqlite")]
+    mod manager_notify_snapshot;
+    #[cfg(feature = "sqlite")]
+    mod manager_sweep;
+    #[cfg(feature = "postgres")]
+    mod runner_claims;
+    #[cfg(feature = "sqlite")]
+    mod runner_retention;
+    #[cfg(feature = "sqlite")]
+    mod runner_stability;
+    #[cfg(feature = "sqlite")]
+    mod runner_targets;
+    #[cfg(feature = "sqlite")]
+    mod runner_wake;
     mod targets;
     mod triggers;
     #[cfg(feature = "sqlite")]
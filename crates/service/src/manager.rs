@@ -1,28 +1,47 @@
 use super::runner::PulseRunner;
-
-use crate::settings::triggers::Trigger;
+use crate::circuit::{CircuitBreakers, TargetHealth};
+use crate::hasher::FileHasher;
+use crate::metrics::Metrics;
+
+use crate::settings::triggers::{
+    notify::{Notify as NotifyTrigger, NotifyEvent},
+    sweep::Sweep,
+    Trigger,
+};
 use crate::settings::webhooks::{EventType, WebhookManager};
 use crate::settings::Settings;
+use crate::stability::{FileState, StabilityTracker};
 
 use autopulse_database::diesel::sql_types::{BigInt, Text};
 use autopulse_database::diesel::QueryableByName;
+use autopulse_database::schema::scan_event_targets::{
+    dsl::scan_event_targets, event_id, next_retry_at as target_retry_at, status as target_status,
+    target,
+};
 use autopulse_database::schema::scan_events::{
-    created_at, event_source, file_path, id, next_retry_at, processed_at, targets_hit, updated_at,
+    created_at, event_source, file_path, id, next_retry_at, processed_at, updated_at,
 };
 use autopulse_database::{
     conn::{get_conn, DbPool},
     diesel::{
-        self, EscapeExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
-        TextExpressionMethods,
+        self, BoolExpressionMethods, EscapeExpressionMethods, ExpressionMethods, OptionalExtension,
+        QueryDsl, RunQueryDsl, TextExpressionMethods,
+    },
+    models::{
+        AppState, EventKind, FoundStatus, NewScanEvent, NotifySnapshot, ProcessStatus, ScanEvent,
+        ScanEventAttempt, ScanEventTarget,
     },
-    models::{FoundStatus, NewScanEvent, ProcessStatus, ScanEvent},
     schema::scan_events::{dsl::scan_events, process_status},
 };
+use autopulse_utils::sify;
 use notify_debouncer_full::notify;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::{select, sync::broadcast};
+use tokio::{
+    select,
+    sync::{broadcast, Notify},
+};
 use tracing::{debug, error, info, warn};
 
 /// Escape LIKE metacharacters so user input is matched literally.
@@ -33,6 +52,15 @@ fn escape_like_pattern(input: &str) -> String {
         .replace('_', "\\_")
 }
 
+fn notify_snapshot(name: &str, path: String, state: &FileState) -> NotifySnapshot {
+    NotifySnapshot {
+        trigger_name: name.to_string(),
+        path,
+        size: state.size as i64,
+        modified: state.modified.unwrap_or_default(),
+    }
+}
+
 // Postgres LIKE is case-sensitive; SQLite's is case-insensitive for ASCII.
 // LOWER() on both sides normalizes so the live SSE filter and the DB query
 // can't disagree on whether a path matches.
@@ -58,6 +86,21 @@ pub struct Stats {
     /// The number of file events that are pending.
     #[diesel(sql_type = BigInt)]
     pub pending: i64,
+    /// The number of file events pruned before they were processed or failed.
+    #[diesel(sql_type = BigInt)]
+    pub expired: i64,
+}
+
+/// Circuit breaker health of a configured target.
+#[derive(Clone, Serialize)]
+pub struct TargetStatus {
+    /// The name of the target as defined in the settings.
+    pub name: String,
+    /// The type of the target, e.g. `plex`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(flatten)]
+    pub health: TargetHealth,
 }
 
 /// One state transition for the in-process broadcast bus.
@@ -68,20 +111,46 @@ pub struct EventBroadcast {
     pub at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Postgres channel the shared bus is relayed on.
+const BUS_CHANNEL: &str = "autopulse_events";
+
+/// What the shared bus sends for an [`EventBroadcast`], the event itself is loaded by the receiver.
+#[derive(Serialize, Deserialize)]
+struct BusMessage {
+    instance: String,
+    id: String,
+    kind: EventType,
+}
+
 #[derive(Clone)]
 pub struct PulseManager {
     pub settings: Arc<Settings>,
     pub pool: Arc<DbPool>,
     pub webhooks: Arc<WebhookManager>,
+    pub metrics: Arc<Metrics>,
+    pub circuits: Arc<CircuitBreakers>,
+    pub stability: Arc<StabilityTracker>,
+    pub hasher: Arc<FileHasher>,
     /// In-process broadcast bus; cloned `PulseManager`s share it.
     pub bus: broadcast::Sender<EventBroadcast>,
+    /// Wakes the runner before its next scheduled pass.
+    pub wake: Arc<Notify>,
+    /// Identifies this instance in the events it claims from a shared database.
+    pub instance_id: Arc<String>,
 }
 
 impl PulseManager {
     pub fn new(settings: Settings, pool: DbPool) -> Self {
         let settings = Arc::new(settings);
         let pool = Arc::new(pool);
-        let webhooks = Arc::new(WebhookManager::new(settings.clone()));
+        let metrics = Arc::new(Metrics::new().expect("metrics should register"));
+        let webhooks = Arc::new(WebhookManager::new(settings.clone(), metrics.clone()));
+        let circuits = Arc::new(CircuitBreakers::new(
+            settings.opts.circuit_threshold,
+            settings.opts.circuit_cooldown,
+        ));
+        let stability = Arc::new(StabilityTracker::new(settings.opts.stability.clone()));
+        let hasher = Arc::new(FileHasher::new(settings.opts.hash_concurrency));
 
         // Capacity 1024: absorbs a Sonarr season-import burst (~50
         // events) with headroom; failure mode under genuine overload
@@ -93,7 +162,13 @@ impl PulseManager {
             settings,
             pool,
             webhooks,
+            metrics,
+            circuits,
+            stability,
+            hasher,
             bus,
+            wake: Arc::new(Notify::new()),
+            instance_id: Arc::new(autopulse_utils::generate_uuid()),
         }
     }
 
@@ -103,6 +178,12 @@ impl PulseManager {
 
     /// `send` error (no subscribers) is swallowed.
     pub fn publish(&self, kind: EventType, event: &ScanEvent) {
+        if self.settings.app.shared_bus {
+            if let Err(e) = self.notify_bus(&kind, event) {
+                warn!("failed to relay event to other instances: {e:?}");
+            }
+        }
+
         let _ = self.bus.send(EventBroadcast {
             kind,
             event: event.clone(),
@@ -110,17 +191,51 @@ impl PulseManager {
         });
     }
 
+    fn notify_bus(&self, kind: &EventType, event: &ScanEvent) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(&BusMessage {
+            instance: self.instance_id.to_string(),
+            id: event.id.clone(),
+            kind: kind.clone(),
+        })?;
+
+        get_conn(&self.pool)?.notify(BUS_CHANNEL, &payload)
+    }
+
+    /// Re-broadcasts an event published by another instance on the shared bus.
+    ///
+    /// Returns whether it was broadcast, messages from this instance and events that
+    /// no longer exist are skipped.
+    pub fn relay(&self, payload: &str) -> anyhow::Result<bool> {
+        let message = serde_json::from_str::<BusMessage>(payload)?;
+
+        if message.instance == *self.instance_id {
+            return Ok(false);
+        }
+
+        let Some(event) = self.get_event(&message.id)? else {
+            return Ok(false);
+        };
+
+        let _ = self.bus.send(EventBroadcast {
+            kind: message.kind,
+            event,
+            at: chrono::Utc::now(),
+        });
+
+        Ok(true)
+    }
+
     /// Manual retry. Pending is excluded so we never clobber an event the
     /// runner is mid-pipeline (would dispatch duplicate target scans — the
     /// thing this service exists to prevent).
     ///
-    /// Complete events also clear `targets_hit` and `processed_at`: every
-    /// target is in `targets_hit`, so the runner's "skip already-hit" filter
-    /// would otherwise produce a no-op. Failed/Retry keep their partial
-    /// `targets_hit` so retry only redoes the targets that actually failed.
+    /// Complete events also drop their target deliveries and `processed_at`:
+    /// every target is complete, so the runner would otherwise produce a
+    /// no-op. Failed/Retry only re-queue the deliveries that did not complete
+    /// so retry only redoes the targets that actually failed.
     ///
-    /// `failed_times` is preserved — manual retry is an impulse, not an
-    /// erasure of history.
+    /// `failed_times` and per-target `attempts` are preserved — manual retry
+    /// is an impulse, not an erasure of history.
     pub fn reschedule_event(&self, ev_id: &str) -> anyhow::Result<ScanEvent> {
         let now = chrono::Utc::now().naive_utc();
         let current: ScanEvent = scan_events
@@ -134,51 +249,115 @@ impl PulseManager {
         let status = ProcessStatus::from_str(&current.process_status)
             .map_err(|()| anyhow::anyhow!("event {ev_id} has unknown process_status"))?;
 
-        let updated: ScanEvent = match status {
-            ProcessStatus::Complete => diesel::update(scan_events.find(ev_id))
-                .set((
-                    process_status.eq::<String>(ProcessStatus::Retry.into()),
-                    next_retry_at.eq(Some(now)),
-                    updated_at.eq(now),
-                    targets_hit.eq(String::new()),
-                    processed_at.eq::<Option<chrono::NaiveDateTime>>(None),
-                ))
-                .get_result(&mut get_conn(&self.pool)?)?,
-            ProcessStatus::Failed | ProcessStatus::Retry => diesel::update(scan_events.find(ev_id))
+        match status {
+            ProcessStatus::Complete => {
+                diesel::delete(scan_event_targets.filter(event_id.eq(ev_id)))
+                    .execute(&mut get_conn(&self.pool)?)?;
+
+                diesel::update(scan_events.find(ev_id))
+                    .set((
+                        process_status.eq::<String>(ProcessStatus::Retry.into()),
+                        next_retry_at.eq(Some(now)),
+                        updated_at.eq(now),
+                        processed_at.eq::<Option<chrono::NaiveDateTime>>(None),
+                    ))
+                    .execute(&mut get_conn(&self.pool)?)?;
+            }
+            ProcessStatus::Failed | ProcessStatus::Retry => {
+                diesel::update(
+                    scan_event_targets
+                        .filter(event_id.eq(ev_id))
+                        .filter(target_status.ne::<String>(ProcessStatus::Complete.into())),
+                )
                 .set((
-                    process_status.eq::<String>(ProcessStatus::Retry.into()),
-                    next_retry_at.eq(Some(now)),
-                    updated_at.eq(now),
+                    target_status.eq::<String>(ProcessStatus::Retry.into()),
+                    target_retry_at.eq(Some(now)),
                 ))
-                .get_result(&mut get_conn(&self.pool)?)?,
+                .execute(&mut get_conn(&self.pool)?)?;
+
+                diesel::update(scan_events.find(ev_id))
+                    .set((
+                        process_status.eq::<String>(ProcessStatus::Retry.into()),
+                        next_retry_at.eq(Some(now)),
+                        updated_at.eq(now),
+                    ))
+                    .execute(&mut get_conn(&self.pool)?)?;
+            }
             ProcessStatus::Pending => {
                 anyhow::bail!("event {ev_id} is not in a retryable state")
             }
-        };
+        }
+
+        // Read back rather than RETURNING, which MySQL lacks
+        let updated = scan_events
+            .find(ev_id)
+            .first::<ScanEvent>(&mut get_conn(&self.pool)?)?;
 
         self.publish(EventType::Retrying, &updated);
+        self.wake.notify_one();
         Ok(updated)
     }
 
+    /// Counts of the stored events, plus those pruned by the retention policy.
+    ///
+    /// Pruned events count as processed or failed when they were, the rest as expired, so the
+    /// statuses add up to the total.
     pub fn get_stats(&self) -> anyhow::Result<Stats> {
         diesel::sql_query(
             "SELECT \
+                e.total + d.total as total, \
+                e.processed + d.processed as processed, \
+                e.retrying as retrying, \
+                e.failed + d.failed as failed, \
+                e.pending as pending, \
+                d.total - d.processed - d.failed as expired \
+            FROM (SELECT \
                 COUNT(*) as total, \
                 COALESCE(SUM(CASE WHEN process_status = 'complete' THEN 1 ELSE 0 END), 0) as processed, \
                 COALESCE(SUM(CASE WHEN process_status = 'retry' THEN 1 ELSE 0 END), 0) as retrying, \
                 COALESCE(SUM(CASE WHEN process_status = 'failed' THEN 1 ELSE 0 END), 0) as failed, \
                 COALESCE(SUM(CASE WHEN process_status = 'pending' THEN 1 ELSE 0 END), 0) as pending \
-            FROM scan_events",
+            FROM scan_events) e, (SELECT \
+                COALESCE(SUM(events), 0) as total, \
+                COALESCE(SUM(CASE WHEN process_status = 'complete' THEN events ELSE 0 END), 0) as processed, \
+                COALESCE(SUM(CASE WHEN process_status = 'failed' THEN events ELSE 0 END), 0) as failed \
+            FROM scan_event_daily_counts) d",
         )
         .get_result::<Stats>(&mut get_conn(&self.pool)?)
         .map_err(Into::into)
     }
 
+    /// Health of every configured target, sorted by name.
+    pub fn get_target_statuses(&self) -> Vec<TargetStatus> {
+        let mut statuses = self
+            .settings
+            .targets
+            .iter()
+            .map(|(name, t)| TargetStatus {
+                name: name.clone(),
+                kind: serde_json::to_value(t)
+                    .ok()
+                    .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(String::from))
+                    .unwrap_or_default(),
+                health: self.circuits.health(name),
+            })
+            .collect::<Vec<_>>();
+
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+
+        statuses
+    }
+
     pub fn add_event(&self, ev: &NewScanEvent) -> anyhow::Result<ScanEvent> {
         let now = chrono::Utc::now().naive_utc();
         let result = get_conn(&self.pool)?.upsert_pending(ev, now)?;
 
+        self.metrics
+            .events_ingested
+            .with_label_values(&[ev.event_source.as_str()])
+            .inc();
         self.publish(EventType::New, &result);
+        self.wake.notify_one();
 
         Ok(result)
     }
@@ -192,6 +371,28 @@ impl PulseManager {
             .map_err(Into::into)
     }
 
+    /// Per-target delivery state of an event, ordered by target name.
+    pub fn get_event_targets(&self, ev_id: &str) -> anyhow::Result<Vec<ScanEventTarget>> {
+        scan_event_targets
+            .filter(event_id.eq(ev_id))
+            .order(target.asc())
+            .load::<ScanEventTarget>(&mut get_conn(&self.pool)?)
+            .map_err(Into::into)
+    }
+
+    /// Delivery attempt history of an event, newest first.
+    pub fn get_event_attempts(&self, ev_id: &str) -> anyhow::Result<Vec<ScanEventAttempt>> {
+        use autopulse_database::schema::scan_event_attempts::dsl::{
+            attempted_at, event_id as attempt_event_id, scan_event_attempts,
+        };
+
+        scan_event_attempts
+            .filter(attempt_event_id.eq(ev_id))
+            .order(attempted_at.desc())
+            .load::<ScanEventAttempt>(&mut get_conn(&self.pool)?)
+            .map_err(Into::into)
+    }
+
     /// Total matching rows for pagination (independent of LIMIT/OFFSET).
     pub fn count_events(
         &self,
@@ -285,11 +486,15 @@ impl PulseManager {
 
     pub async fn start(&self) -> anyhow::Result<()> {
         let mut runner = PulseRunner::new(self);
-        let mut timer = tokio::time::interval(std::time::Duration::from_secs(1));
+        let poll_interval = std::time::Duration::from_secs(self.settings.opts.poll_interval);
         let mut consecutive_errors: u32 = 0;
 
         loop {
-            match runner.run().await {
+            let loop_timer = self.metrics.runner_loop_seconds.start_timer();
+            let result = runner.run().await;
+            loop_timer.observe_duration();
+
+            match result {
                 Ok(()) => {
                     consecutive_errors = 0;
                 }
@@ -302,10 +507,26 @@ impl PulseManager {
                     let backoff = std::cmp::min(1u64 << shift, 60);
                     error!("event processing error (retry in {backoff}s): {e:?}");
                     tokio::time::sleep(std::time::Duration::from_secs(backoff)).await;
+                    continue;
                 }
             }
 
-            timer.tick().await;
+            let sleep = match runner.next_wake() {
+                Ok(Some(at)) => (at - chrono::Utc::now().naive_utc())
+                    .to_std()
+                    .unwrap_or_default()
+                    .min(poll_interval),
+                Ok(None) => poll_interval,
+                Err(e) => {
+                    error!("failed to schedule the next run: {e:?}");
+                    poll_interval
+                }
+            };
+
+            select! {
+                () = self.wake.notified() => {}
+                () = tokio::time::sleep(sleep) => {}
+            }
         }
     }
 
@@ -322,6 +543,69 @@ impl PulseManager {
         }
     }
 
+    /// Relays events other instances publish on the shared bus to this instance's subscribers.
+    pub async fn start_bus(&self) -> anyhow::Result<()> {
+        if !self.settings.app.shared_bus {
+            return futures::future::pending().await;
+        }
+
+        if !self.settings.app.database_url.starts_with("postgres") {
+            warn!("app.shared_bus needs a Postgres database, live updates stay local");
+            return futures::future::pending().await;
+        }
+
+        #[cfg(not(feature = "postgres"))]
+        {
+            warn!("app.shared_bus needs Postgres support, live updates stay local");
+            futures::future::pending().await
+        }
+
+        #[cfg(feature = "postgres")]
+        loop {
+            if let Err(e) = self.listen_bus().await {
+                error!("shared bus listener failed, reconnecting: {e:?}");
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn listen_bus(&self) -> anyhow::Result<()> {
+        use autopulse_database::conn::Listener;
+
+        let url = self.settings.app.database_url.clone();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        // Polling the listener blocks on its connection, so it runs on its own thread and only
+        // the payloads come back to be relayed here
+        let listening = tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let mut listener = Listener::new(&url, BUS_CHANNEL)?;
+
+            debug!("listening for events from other instances");
+
+            while !tx.is_closed() {
+                for payload in listener.drain()? {
+                    tx.send(payload)?;
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(250));
+            }
+
+            Ok(())
+        });
+
+        while let Some(payload) = rx.recv().await {
+            if let Err(e) = self.relay(&payload) {
+                warn!("failed to relay event from another instance: {e:?}");
+            }
+        }
+
+        listening
+            .await
+            .map_err(|e| anyhow::anyhow!("listener task failed: {e}"))?
+    }
+
     pub async fn start_notify(&self) -> anyhow::Result<()> {
         let (global_tx, mut global_rx) = tokio::sync::mpsc::unbounded_channel();
 
@@ -348,20 +632,52 @@ impl PulseManager {
                     as i64;
 
                 let global_tx = global_tx.clone();
+                let queue_depth = self.metrics.notify_queue_depth.clone();
 
                 let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
 
                 let service_clone = service.clone();
 
-                producers.push(tokio::spawn(async move { service_clone.watcher(tx).await }));
+                let (seen_tx, mut seen_rx) = tokio::sync::mpsc::unbounded_channel();
+
+                let watcher_tx = tx.clone();
                 producers.push(tokio::spawn(async move {
-                    while let Some((path, reason)) = rx.recv().await {
+                    service_clone
+                        .observed_watcher(watcher_tx, Some(seen_tx))
+                        .await
+                }));
+
+                let manager = self.clone();
+                let trigger_name = name.clone();
+                producers.push(tokio::spawn(async move {
+                    // Live events are buffered until the startup snapshot is stored so they win over it
+                    match manager.reconcile_notify(&trigger_name, &service, tx).await {
+                        Ok(0) => {}
+                        Ok(changes) => {
+                            info!("reconciled {changes} change(s) for '{trigger_name}' since last run")
+                        }
+                        Err(e) => error!("failed to reconcile '{trigger_name}' trigger: {e:?}"),
+                    }
+
+                    while let Some(path) = seen_rx.recv().await {
+                        if let Err(e) = manager.record_notify_path(&trigger_name, &path) {
+                            warn!("failed to update snapshot of '{}': {e:?}", path.display());
+                        }
+                    }
+
+                    Ok::<(), anyhow::Error>(())
+                }));
+                producers.push(tokio::spawn(async move {
+                    while let Some(event) = rx.recv().await {
+                        // Counted before sending so the consumer never sees a negative depth
+                        queue_depth.inc();
+
                         if let Err(e) = global_tx.send((
                             name.clone(),
-                            path,
-                            reason,
+                            event,
                             chrono::Utc::now().naive_utc() + chrono::Duration::seconds(timer),
                         )) {
+                            queue_depth.dec();
                             error!("failed to send notify event: {:?}", e);
                         }
                     }
@@ -374,29 +690,45 @@ impl PulseManager {
         let manager = Arc::new(self.clone());
 
         let consumer = async move {
-            while let Some((name, path, reason, when_process)) = global_rx.recv().await {
+            while let Some((name, event, when_process)) = global_rx.recv().await {
+                manager.metrics.notify_queue_depth.dec();
+
+                let NotifyEvent {
+                    path,
+                    kind,
+                    previous_path,
+                } = event;
+
+                let event_kind = if previous_path.is_some() {
+                    EventKind::Renamed
+                } else {
+                    match kind {
+                        notify::EventKind::Create(_)
+                        | notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                            notify::event::RenameMode::To,
+                        )) => EventKind::Created,
+                        notify::EventKind::Remove(_)
+                        | notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                            notify::event::RenameMode::From,
+                        )) => EventKind::Deleted,
+                        _ => EventKind::Modified,
+                    }
+                };
+
                 let new_scan_event = NewScanEvent {
                     event_source: name.clone(),
                     file_path: path.clone(),
                     can_process: when_process,
                     found_status: FoundStatus::Found.into(),
+                    event_kind: event_kind.into(),
+                    previous_path,
                     ..Default::default()
                 };
 
                 match manager.add_event(&new_scan_event) {
                     Err(e) => error!("failed to add notify event: {:?}", e),
                     Ok(_) => {
-                        info!(
-                            "added 1 {} file from {} trigger",
-                            match reason {
-                                notify::EventKind::Create(_) => "created",
-                                notify::EventKind::Modify(_) => "modified",
-                                notify::EventKind::Remove(_) => "removed",
-                                notify::EventKind::Access(_) => "accessed",
-                                notify::EventKind::Any | notify::EventKind::Other => "changed",
-                            },
-                            name,
-                        );
+                        info!("added 1 {} file from {} trigger", event_kind, name);
 
                         debug!("file '{}' added from '{}' trigger", path, name);
                     }
@@ -424,4 +756,206 @@ impl PulseManager {
 
         Ok(())
     }
+
+    /// Compares a notify trigger's paths against the snapshot stored on its last run and sends
+    /// what changed while it was not watching to `tx`, returning how many changes there were.
+    ///
+    /// Without a stored snapshot only a baseline is recorded.
+    pub async fn reconcile_notify(
+        &self,
+        name: &str,
+        trigger: &NotifyTrigger,
+        tx: tokio::sync::mpsc::UnboundedSender<NotifyEvent>,
+    ) -> anyhow::Result<usize> {
+        use autopulse_database::schema::notify_snapshots::dsl::{notify_snapshots, trigger_name};
+
+        let previous = notify_snapshots
+            .filter(trigger_name.eq(name))
+            .load::<NotifySnapshot>(&mut get_conn(&self.pool)?)?
+            .into_iter()
+            .map(|row| {
+                let state = FileState {
+                    size: row.size as u64,
+                    modified: Some(row.modified),
+                };
+                (row.path, state)
+            })
+            .collect::<std::collections::HashMap<_, _>>();
+
+        let scanner = trigger.clone();
+        let current = tokio::task::spawn_blocking(move || scanner.snapshot())
+            .await
+            .map_err(|e| anyhow::anyhow!("snapshot task failed: {e}"))?;
+
+        let changes = if previous.is_empty() {
+            info!("no snapshot of '{name}' trigger yet, recording a baseline");
+            0
+        } else {
+            trigger.reconcile(tx, &previous, &current)?
+        };
+
+        let rows = current
+            .into_iter()
+            .map(|(path, state)| notify_snapshot(name, path, &state))
+            .collect::<Vec<_>>();
+
+        get_conn(&self.pool)?.replace_snapshots(name, &rows)?;
+
+        Ok(changes)
+    }
+
+    /// Brings the stored snapshot of a watched path up to date after a live event.
+    pub fn record_notify_path(&self, name: &str, path: &std::path::Path) -> anyhow::Result<()> {
+        use autopulse_database::schema::notify_snapshots::dsl::{
+            notify_snapshots, path as snapshot_path, trigger_name,
+        };
+
+        let mut conn = get_conn(&self.pool)?;
+        let key = path.to_string_lossy().to_string();
+
+        match FileState::read(path) {
+            Ok(state) if path.is_file() => {
+                conn.save_snapshot(&notify_snapshot(name, key, &state))?;
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // A removed directory takes everything below it along
+                let prefix = format!("{}{}", escape_like_pattern(&key), std::path::MAIN_SEPARATOR);
+
+                diesel::delete(
+                    notify_snapshots.filter(trigger_name.eq(name)).filter(
+                        snapshot_path
+                            .eq(&key)
+                            .or(snapshot_path.like(format!("{prefix}%")).escape('\\')),
+                    ),
+                )
+                .execute(&mut conn)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(())
+    }
+
+    /// Time the last sweep of a trigger started, `None` before its first sweep.
+    pub fn last_sweep(&self, trigger: &str) -> anyhow::Result<Option<chrono::NaiveDateTime>> {
+        use autopulse_database::schema::app_state::dsl::app_state;
+
+        let Some(row) = app_state
+            .find(format!("sweep:{trigger}"))
+            .first::<AppState>(&mut get_conn(&self.pool)?)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let at = chrono::DateTime::parse_from_rfc3339(&String::from_utf8_lossy(&row.value))?;
+
+        Ok(Some(at.naive_utc()))
+    }
+
+    /// Sweeps a trigger once, returning how many files were queued.
+    ///
+    /// The first sweep only records when it ran, so existing libraries are not queued in full.
+    pub async fn sweep(&self, name: &str, sweep: &Sweep) -> anyhow::Result<usize> {
+        let started = chrono::Utc::now();
+        let last = self.last_sweep(name)?;
+
+        let paths = match last {
+            Some(since) => {
+                let sweep = sweep.clone();
+                tokio::task::spawn_blocking(move || sweep.scan(since))
+                    .await
+                    .map_err(|e| anyhow::anyhow!("sweep task failed: {e}"))??
+            }
+            None => {
+                info!("first sweep of '{name}' trigger, files modified from now on will be queued");
+                vec![]
+            }
+        };
+
+        let wait = sweep
+            .timer
+            .clone()
+            .unwrap_or_default()
+            .wait
+            .unwrap_or(self.settings.opts.default_timer_wait) as i64;
+
+        for path in &paths {
+            self.add_event(&NewScanEvent {
+                event_source: name.to_string(),
+                file_path: path.clone(),
+                can_process: chrono::Utc::now().naive_utc() + chrono::Duration::seconds(wait),
+                found_status: FoundStatus::Found.into(),
+                event_kind: EventKind::Modified.into(),
+                ..Default::default()
+            })?;
+
+            debug!("file '{}' added from '{}' trigger", path, name);
+        }
+
+        if !paths.is_empty() {
+            info!(
+                "added {} file{} from {} trigger",
+                paths.len(),
+                sify(&paths),
+                name
+            );
+
+            self.webhooks
+                .add_event(EventType::New, Some(name.to_string()), &paths)
+                .await;
+        }
+
+        // Stored only once the sweep completed, a failed sweep is retried from the same point
+        get_conn(&self.pool)?.save_state(&AppState {
+            key: format!("sweep:{name}"),
+            value: started.to_rfc3339().into_bytes(),
+            updated_at: started.naive_utc(),
+        })?;
+
+        Ok(paths.len())
+    }
+
+    pub async fn start_sweeps(&self) -> anyhow::Result<()> {
+        let sweeps = self
+            .settings
+            .triggers
+            .iter()
+            .filter_map(|(name, trigger)| match trigger {
+                Trigger::Sweep(sweep) => Some((name.clone(), sweep.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if sweeps.is_empty() {
+            return futures::future::pending().await;
+        }
+
+        let tasks = sweeps.into_iter().map(|(name, sweep)| {
+            let manager = self.clone();
+
+            async move {
+                let schedule = sweep.cron()?;
+
+                // Asked again after every sweep so a slow sweep skips the runs it overlapped
+                while let Some(next) = schedule.upcoming(chrono::Utc).next() {
+                    let delay = (next - chrono::Utc::now()).to_std().unwrap_or_default();
+                    tokio::time::sleep(delay).await;
+
+                    if let Err(e) = manager.sweep(&name, &sweep).await {
+                        error!("failed to sweep '{name}' trigger: {e}");
+                    }
+                }
+
+                Ok::<(), anyhow::Error>(())
+            }
+        });
+
+        for result in futures::future::join_all(tasks).await {
+            result?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,98 @@
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+/// Process latencies range from a local command to a slow media server scan.
+const PROCESS_BUCKETS: &[f64] = &[
+    0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0,
+];
+
+/// Prometheus metrics for the service, exposed at `/metrics`.
+///
+/// Each [`PulseManager`](crate::manager::PulseManager) owns its own registry so
+/// managers built in tests do not share counters.
+pub struct Metrics {
+    registry: Registry,
+
+    /// Events added per trigger.
+    pub events_ingested: IntCounterVec,
+    /// Per-target delivery outcomes, labelled `complete`, `retry` or `failed`.
+    pub target_events: IntCounterVec,
+    /// Time taken by a target to process a batch of events.
+    pub target_process_seconds: HistogramVec,
+    /// Webhook batch sends, labelled `success` or `failure`.
+    pub webhook_sends: IntCounterVec,
+    /// Time taken by a single pass of the runner.
+    pub runner_loop_seconds: Histogram,
+    /// Notify events waiting to be added to the database.
+    pub notify_queue_depth: IntGauge,
+    /// Whether every configured anchor is present (1) or not (0).
+    pub anchors_available: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> anyhow::Result<Self> {
+        let registry = Registry::new_custom(Some("autopulse".to_string()), None)?;
+
+        let events_ingested = IntCounterVec::new(
+            Opts::new("events_ingested_total", "Events added per trigger"),
+            &["trigger"],
+        )?;
+        let target_events = IntCounterVec::new(
+            Opts::new("target_events_total", "Event delivery outcomes per target"),
+            &["target", "outcome"],
+        )?;
+        let target_process_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "target_process_duration_seconds",
+                "Time taken by a target to process a batch of events",
+            )
+            .buckets(PROCESS_BUCKETS.to_vec()),
+            &["target"],
+        )?;
+        let webhook_sends = IntCounterVec::new(
+            Opts::new("webhook_sends_total", "Webhook batch send outcomes"),
+            &["webhook", "outcome"],
+        )?;
+        let runner_loop_seconds = Histogram::with_opts(HistogramOpts::new(
+            "runner_loop_duration_seconds",
+            "Time taken by a single pass of the runner",
+        ))?;
+        let notify_queue_depth = IntGauge::new(
+            "notify_queue_depth",
+            "Notify events waiting to be added to the database",
+        )?;
+        let anchors_available = IntGauge::new(
+            "anchors_available",
+            "Whether every configured anchor is present",
+        )?;
+
+        registry.register(Box::new(events_ingested.clone()))?;
+        registry.register(Box::new(target_events.clone()))?;
+        registry.register(Box::new(target_process_seconds.clone()))?;
+        registry.register(Box::new(webhook_sends.clone()))?;
+        registry.register(Box::new(runner_loop_seconds.clone()))?;
+        registry.register(Box::new(notify_queue_depth.clone()))?;
+        registry.register(Box::new(anchors_available.clone()))?;
+
+        anchors_available.set(1);
+
+        Ok(Self {
+            registry,
+            events_ingested,
+            target_events,
+            target_process_seconds,
+            webhook_sends,
+            runner_loop_seconds,
+            notify_queue_depth,
+            anchors_available,
+        })
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    pub fn encode(&self) -> anyhow::Result<String> {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .map_err(Into::into)
+    }
+}
@@ -1,29 +1,44 @@
+use crate::archive;
+use crate::circuit::{Admission, Transition};
+use crate::coalesce::coalesce;
 use crate::manager::PulseManager;
-use crate::settings::targets::TargetProcess;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{
+    dispatch_stages, ProcessError, ProcessResult, Target, TargetProcess,
+};
 use crate::settings::webhooks::EventType;
 use autopulse_database::{
-    conn::get_conn,
+    conn::{get_conn, Expiry},
     diesel::{self, BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl},
-    models::{FoundStatus, ProcessStatus, ScanEvent},
+    models::{FoundStatus, ProcessStatus, ScanEvent, ScanEventAttempt, ScanEventTarget},
+    schema::scan_event_targets::{dsl::scan_event_targets, event_id},
     schema::scan_events::{
-        can_process, created_at, dsl::scan_events, found_status, next_retry_at, process_status,
+        can_process, claimed_until, dsl::scan_events, found_status, next_retry_at, process_status,
     },
 };
-use autopulse_utils::sha256checksum;
-use autopulse_utils::sify;
+use autopulse_utils::{generate_uuid, sify, FileHash, RuntimePath};
+use futures::StreamExt;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
-use tracing::{debug, error, info, info_span, warn, Instrument};
+use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 
 enum FileCheckResult {
     NotFound,
+    Unstable,
     Found,
     HashMatch,
     HashMismatch,
 }
 
+/// Time between cleanup passes.
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+/// Batches of expired events deleted per cleanup pass.
+const CLEANUP_BATCHES: usize = 10;
+
 pub(super) struct PulseRunner<'a> {
     manager: &'a PulseManager,
     anchors_available: bool,
+    last_cleanup: Option<std::time::Instant>,
 }
 
 impl<'a> PulseRunner<'a> {
@@ -31,10 +46,55 @@ impl<'a> PulseRunner<'a> {
         Self {
             manager,
             anchors_available: true,
+            last_cleanup: None,
+        }
+    }
+
+    async fn check_file(&self, ev: &ScanEvent) -> anyhow::Result<FileCheckResult> {
+        let file_path = PathBuf::from(&ev.file_path);
+
+        let path_clone = file_path.clone();
+        let stability = self.manager.stability.clone();
+        let ev_id = ev.id.clone();
+        let result = tokio::task::spawn_blocking(move || -> anyhow::Result<FileCheckResult> {
+            if !path_clone.exists() {
+                stability.forget(&ev_id);
+                return Ok(FileCheckResult::NotFound);
+            }
+            // Hash only once the file stopped changing, a partial copy never matches
+            if !stability.observe(&ev_id, &path_clone, chrono::Utc::now().naive_utc())? {
+                return Ok(FileCheckResult::Unstable);
+            }
+            Ok(FileCheckResult::Found)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("file check task failed: {e}"))??;
+
+        let Some(hash) = ev
+            .file_hash
+            .as_ref()
+            .filter(|_| matches!(result, FileCheckResult::Found))
+        else {
+            return Ok(result);
+        };
+
+        let expected = match hash.parse::<FileHash>() {
+            Ok(expected) => expected,
+            Err(e) => {
+                warn!("event {} has an unusable hash: {e}", ev.id);
+                return Ok(FileCheckResult::HashMismatch);
+            }
+        };
+
+        if self.manager.hasher.matches(&file_path, &expected).await? {
+            self.manager.hasher.forget(&file_path);
+            Ok(FileCheckResult::HashMatch)
+        } else {
+            Ok(FileCheckResult::HashMismatch)
         }
     }
 
-    async fn update_found_status(&self) -> anyhow::Result<()> {
+    pub async fn update_found_status(&self) -> anyhow::Result<()> {
         if !self.manager.settings.opts.check_path {
             return Ok(());
         }
@@ -47,35 +107,47 @@ impl<'a> PulseRunner<'a> {
             .filter(process_status.eq::<String>(ProcessStatus::Pending.into()))
             .load::<ScanEvent>(&mut get_conn(&self.manager.pool)?)?;
 
-        for ev in &mut evs {
-            let file_path = PathBuf::from(&ev.file_path);
+        // Only these events still wait for their file, the caches can let go of any other
+        let waiting = evs.iter().map(|ev| ev.id.as_str()).collect::<HashSet<_>>();
+        let paths = evs
+            .iter()
+            .map(|ev| PathBuf::from(&ev.file_path))
+            .collect::<HashSet<_>>();
+        self.manager.stability.retain(|id| waiting.contains(id));
+        self.manager.hasher.retain(|path| paths.contains(path));
 
-            let expected_hash = ev.file_hash.clone();
-            let path_clone = file_path.clone();
-            let result: FileCheckResult =
-                tokio::task::spawn_blocking(move || -> anyhow::Result<FileCheckResult> {
-                    if !path_clone.exists() {
-                        return Ok(FileCheckResult::NotFound);
-                    }
-                    match expected_hash {
-                        Some(hash) => {
-                            let file_hash = sha256checksum(&path_clone)?;
-                            if hash == file_hash {
-                                Ok(FileCheckResult::HashMatch)
-                            } else {
-                                Ok(FileCheckResult::HashMismatch)
-                            }
-                        }
-                        None => Ok(FileCheckResult::Found),
-                    }
-                })
-                .await
-                .map_err(|e| anyhow::anyhow!("file check task failed: {e}"))??;
+        // Checked concurrently so one large file being hashed does not hold up the rest
+        let results: Vec<anyhow::Result<FileCheckResult>> = futures::stream::iter(&evs)
+            .map(|ev| self.check_file(ev))
+            .buffered(self.manager.settings.opts.hash_concurrency.max(1))
+            .collect()
+            .await;
+
+        for (ev, result) in evs.iter_mut().zip(results) {
+            let result = result?;
 
             let bus_kind: EventType = match result {
                 FileCheckResult::NotFound => {
                     // Nothing transitioned; skip the write so we don't
                     // churn `updated_at` (and UI ordering) every poll.
+                    if ev.found_status == FoundStatus::NotFound.to_string() {
+                        continue;
+                    }
+                    // An unstable or mismatched file was deleted again
+                    debug!("file '{}' went missing", ev.file_path);
+                    ev.found_status = FoundStatus::NotFound.into();
+                    ev.updated_at = chrono::Utc::now().naive_utc();
+                    get_conn(&self.manager.pool)?.save_changes(ev)?;
+                    continue;
+                }
+                FileCheckResult::Unstable => {
+                    if ev.found_status == FoundStatus::Unstable.to_string() {
+                        continue;
+                    }
+                    debug!("file '{}' is still changing", ev.file_path);
+                    ev.found_status = FoundStatus::Unstable.into();
+                    ev.updated_at = chrono::Utc::now().naive_utc();
+                    get_conn(&self.manager.pool)?.save_changes(ev)?;
                     continue;
                 }
                 FileCheckResult::Found | FileCheckResult::HashMatch => {
@@ -144,27 +216,16 @@ impl<'a> PulseRunner<'a> {
     }
 
     pub async fn update_process_status(&self) -> anyhow::Result<()> {
-        let base_query = scan_events
-            .limit(100)
-            .filter(process_status.eq_any([
-                String::from(ProcessStatus::Pending),
-                String::from(ProcessStatus::Retry),
-            ]))
-            .filter(
-                next_retry_at
-                    .is_null()
-                    .or(next_retry_at.lt(chrono::Utc::now().naive_utc())),
-            )
-            // filter by processable events
-            .filter(can_process.lt(chrono::Utc::now().naive_utc()));
-
-        let mut evs = if self.manager.settings.opts.check_path {
-            base_query
-                .filter(found_status.eq::<String>(FoundStatus::Found.into()))
-                .load::<ScanEvent>(&mut get_conn(&self.manager.pool)?)?
-        } else {
-            base_query.load::<ScanEvent>(&mut get_conn(&self.manager.pool)?)?
-        };
+        let now = chrono::Utc::now().naive_utc();
+        let lease = chrono::Duration::seconds(self.manager.settings.opts.claim_lease as i64);
+
+        let mut evs = get_conn(&self.manager.pool)?.claim_due_events(
+            &self.manager.instance_id,
+            now,
+            now + lease,
+            self.manager.settings.opts.check_path,
+            100,
+        )?;
 
         if evs.is_empty() {
             return Ok(());
@@ -247,52 +308,314 @@ impl<'a> PulseRunner<'a> {
         &self,
         evs: &mut [ScanEvent],
     ) -> anyhow::Result<(Vec<ScanEvent>, Vec<ScanEvent>, Vec<ScanEvent>)> {
-        let mut failed_ids = vec![];
-
-        let trigger_settings = &self.manager.settings.triggers;
-
-        for (name, target) in &self.manager.settings.targets {
-            let evs = evs
-                .iter_mut()
-                .filter(|x| !x.get_targets_hit().contains(name))
-                .filter(|x| {
-                    trigger_settings
-                        .get(&x.event_source)
-                        .is_none_or(|trigger| !trigger.excludes().contains(name))
+        let now = chrono::Utc::now().naive_utc();
+        let max_retries = self.manager.settings.opts.max_retries;
+
+        let ids = evs.iter().map(|x| x.id.clone()).collect::<Vec<_>>();
+        let mut deliveries: HashMap<(String, String), ScanEventTarget> = scan_event_targets
+            .filter(event_id.eq_any(&ids))
+            .load::<ScanEventTarget>(&mut get_conn(&self.manager.pool)?)?
+            .into_iter()
+            .map(|d| ((d.event_id.clone(), d.target.clone()), d))
+            .collect();
+
+        let settings = &self.manager.settings;
+        let routing = evs
+            .iter()
+            .map(|ev| {
+                (
+                    ev.id.clone(),
+                    settings.route(&ev.event_source, ev.kind(), &ev.file_path),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let applies = |name: &String, ev: &ScanEvent| routing[&ev.id].contains_key(name);
+
+        let mut failed_ids = HashSet::new();
+        // Events a paused or probing target did not receive, and when to try them again
+        let mut held: HashMap<(String, String), chrono::NaiveDateTime> = HashMap::new();
+        // Events a coalescing target is collecting, and when their directory settles
+        let mut waiting: HashMap<(String, String), chrono::NaiveDateTime> = HashMap::new();
+
+        // A target only gets an event once every applicable target in its `after` has
+        // completed it. Stages run in order, so that can happen within the same tick.
+        let ready = |target: &Target,
+                     ev: &ScanEvent,
+                     deliveries: &HashMap<(String, String), ScanEventTarget>| {
+            target.after().iter().all(|prerequisite| {
+                !applies(prerequisite, ev)
+                    || deliveries
+                        .get(&(ev.id.clone(), prerequisite.clone()))
+                        .is_some_and(|d| d.status == String::from(ProcessStatus::Complete))
+            })
+        };
+
+        let routing = &routing;
+
+        let lease = chrono::Duration::seconds(settings.opts.claim_lease as i64);
+        let target_timeout = settings.opts.target_timeout;
+        // The pass as a whole gets as long as its slowest target, later stages share what the
+        // first one left and whatever does not fit goes to the next pass
+        let deadline = tokio::time::Instant::now()
+            + std::time::Duration::from_secs(
+                settings
+                    .targets
+                    .values()
+                    .map(|target| target.timeout(target_timeout))
+                    .max()
+                    .unwrap_or(target_timeout),
+            );
+
+        for (index, stage) in dispatch_stages(&self.manager.settings.targets)?
+            .into_iter()
+            .enumerate()
+        {
+            let cutoff = (index > 0).then_some(deadline);
+            // Each stage may take up to `target_timeout`, so the lease is renewed before every
+            // one of them rather than once for the whole chain of `after` targets
+            get_conn(&self.manager.pool)?.extend_claims(
+                &self.manager.instance_id,
+                &ids,
+                chrono::Utc::now().naive_utc() + lease,
+            )?;
+
+            let dispatches = stage
+                .into_iter()
+                .map(|name| (name, &self.manager.settings.targets[name]))
+                .map(|(name, target)| {
+                    let mut evs = evs
+                        .iter()
+                        .filter(|x| {
+                            deliveries
+                                .get(&(x.id.clone(), name.clone()))
+                                .is_none_or(|d| d.is_due(now))
+                        })
+                        .filter(|x| applies(name, x))
+                        .filter(|x| ready(target, x, &deliveries))
+                        .collect::<Vec<&ScanEvent>>();
+
+                    let (skipped, retry_at) = match self.manager.circuits.admit(name, now) {
+                        Admission::All => (vec![], now),
+                        Admission::Probe => {
+                            debug!("probing target '{}' with a single event", name);
+                            (evs.split_off(evs.len().min(1)), now)
+                        }
+                        Admission::Paused(until) => {
+                            debug!("target '{}' is paused until {}", name, until);
+                            (std::mem::take(&mut evs), until)
+                        }
+                    };
+
+                    for ev in skipped {
+                        held.insert((ev.id.clone(), name.clone()), retry_at);
+                    }
+
+                    let (evs, send, merged) = match target.coalesce() {
+                        Some(window) => {
+                            let coalesced = coalesce(evs, window, now, |ev| {
+                                RuntimePath::new(&routing[&ev.id][name].path)
+                                    .parent_or_self()
+                                    .as_str()
+                                    .to_string()
+                            });
+
+                            for (ev, until) in coalesced.waiting {
+                                waiting.insert((ev.id.clone(), name.clone()), until);
+                            }
+
+                            for (id, others) in &coalesced.merged {
+                                debug!(
+                                    "coalesced {} events into one directory for target '{}'",
+                                    others.len() + 1,
+                                    name
+                                );
+                                trace!("'{}' stands in for {:?}", id, others);
+                            }
+
+                            (coalesced.members, coalesced.send, coalesced.merged)
+                        }
+                        None => {
+                            let send = evs.iter().map(|ev| (*ev).clone()).collect();
+                            (evs, send, vec![])
+                        }
+                    };
+
+                    (name, target, evs, send, merged)
                 })
-                .filter(|x| target.should_process_event(x))
-                .collect::<Vec<&mut ScanEvent>>();
+                .filter(|(_, _, evs, _, _)| !evs.is_empty())
+                .map(|(name, target, evs, send, merged)| async move {
+                    // Events routed with a rewrite override go out in their own call
+                    let mut groups: BTreeMap<Option<usize>, (Option<&Rewrite>, Vec<&ScanEvent>)> =
+                        BTreeMap::new();
+
+                    for ev in &send {
+                        let routed = &routing[&ev.id][name];
+                        groups
+                            .entry(routed.rewrite.and(routed.route))
+                            .or_insert_with(|| (routed.rewrite, vec![]))
+                            .1
+                            .push(ev);
+                    }
 
-            if evs.is_empty() {
-                continue;
-            }
+                    let started = std::time::Instant::now();
+                    let timeout = target.timeout(target_timeout);
+                    let until =
+                        tokio::time::Instant::now() + std::time::Duration::from_secs(timeout);
+
+                    let res = match cutoff.filter(|cutoff| *cutoff < until) {
+                        Some(cutoff) if cutoff <= tokio::time::Instant::now() => None,
+                        Some(cutoff) => tokio::time::timeout_at(
+                            cutoff,
+                            self.dispatch_routed(target, groups.into_values().collect()),
+                        )
+                        .instrument(info_span!("process ", target = name))
+                        .await
+                        .ok(),
+                        None => Some(
+                            tokio::time::timeout_at(
+                                until,
+                                self.dispatch_routed(target, groups.into_values().collect()),
+                            )
+                            .instrument(info_span!("process ", target = name))
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(anyhow::anyhow!("target timed out after {timeout}s"))
+                            }),
+                        ),
+                    }
+                    .map(|res| {
+                        res.map(|mut result| {
+                            for (id, others) in &merged {
+                                result.share(id, others);
+                            }
+                            result
+                        })
+                    });
 
-            let res = target
-                .process(
-                    // TODO: Somehow clean this up
-                    evs.iter()
-                        .map(|x| &**x)
-                        .collect::<Vec<&ScanEvent>>()
-                        .as_slice(),
-                )
-                .instrument(info_span!("process ", target = name))
-                .await;
+                    (name, evs, res, started.elapsed())
+                });
+
+            let results = futures::future::join_all(dispatches).await;
+
+            for (name, evs, res, elapsed) in results {
+                // Cut off by the end of the pass rather than its own timeout, so not a failure
+                let Some(res) = res else {
+                    debug!("target '{}' deferred to the next pass", name);
 
-            match res {
-                Ok(s) => {
                     for ev in evs {
-                        if s.contains(&ev.id) {
-                            ev.add_target_hit(name);
-                        } else {
-                            failed_ids.push(ev.id.clone());
-                        }
+                        held.insert((ev.id.clone(), name.clone()), now);
                     }
-                }
-                Err(e) => {
-                    failed_ids.extend(evs.iter().map(|x| x.id.clone()));
+                    continue;
+                };
+
+                let duration_ms = elapsed.as_millis() as i64;
+                self.manager
+                    .metrics
+                    .target_process_seconds
+                    .with_label_values(&[name.as_str()])
+                    .observe(elapsed.as_secs_f64());
+                let attempted_at = chrono::Utc::now().naive_utc();
+
+                let outcome = |ev: &ScanEvent| -> Result<(), ProcessError> {
+                    match &res {
+                        Ok(result) => result.outcome(&ev.id),
+                        Err(e) => Err(e.into()),
+                    }
+                };
 
+                if let Err(e) = &res {
                     error!("failed to process target '{}': {:?}", name, e);
                 }
+
+                // The circuit only counts a batch as failed when none of its events got through
+                let circuit_outcome = match &res {
+                    Ok(result) => evs
+                        .iter()
+                        .map(|ev| result.outcome(&ev.id))
+                        .find(Result::is_ok)
+                        .unwrap_or_else(|| outcome(evs[0]))
+                        .map_err(|e| e.message),
+                    Err(e) => Err(ProcessError::from(e).message),
+                };
+
+                match self.manager.circuits.record(name, now, circuit_outcome) {
+                    Some(Transition::Opened) => {
+                        warn!(
+                            "pausing target '{}' for {}s after repeated failures",
+                            name, self.manager.settings.opts.circuit_cooldown
+                        );
+                        self.manager
+                            .webhooks
+                            .add_event(EventType::TargetDown, None, std::slice::from_ref(name))
+                            .await;
+                    }
+                    Some(Transition::Closed) => {
+                        info!("target '{}' recovered, resuming", name);
+                        self.manager
+                            .webhooks
+                            .add_event(EventType::TargetUp, None, std::slice::from_ref(name))
+                            .await;
+                    }
+                    None => {}
+                }
+
+                let mut attempts = Vec::with_capacity(evs.len());
+
+                for ev in evs {
+                    let delivery = deliveries
+                        .entry((ev.id.clone(), name.clone()))
+                        .or_insert_with(|| ScanEventTarget::new(&ev.id, name, now));
+
+                    delivery.attempts += 1;
+                    delivery.updated_at = now;
+
+                    let outcome = outcome(ev);
+
+                    attempts.push(ScanEventAttempt {
+                        id: generate_uuid(),
+                        event_id: ev.id.clone(),
+                        target: name.clone(),
+                        success: outcome.is_ok(),
+                        http_status: outcome.as_ref().err().and_then(|e| e.status).map(i32::from),
+                        error: outcome.as_ref().err().map(|e| e.message.clone()),
+                        duration_ms,
+                        attempted_at,
+                    });
+
+                    match outcome {
+                        Ok(()) => {
+                            delivery.status = ProcessStatus::Complete.into();
+                            delivery.next_retry_at = None;
+                            delivery.last_error = None;
+                        }
+                        Err(e) => {
+                            failed_ids.insert(ev.id.clone());
+
+                            if delivery.attempts >= max_retries {
+                                delivery.status = ProcessStatus::Failed.into();
+                                delivery.next_retry_at = None;
+                            } else {
+                                delivery.status = ProcessStatus::Retry.into();
+                                delivery.next_retry_at = Some(
+                                    now + chrono::Duration::seconds(
+                                        2_i64.pow(delivery.attempts as u32 + 1),
+                                    ),
+                                );
+                            }
+                            delivery.last_error = Some(e.message);
+                        }
+                    }
+
+                    self.manager
+                        .metrics
+                        .target_events
+                        .with_label_values(&[name.as_str(), delivery.status.as_str()])
+                        .inc();
+
+                    get_conn(&self.manager.pool)?.save_target(delivery)?;
+                }
+
+                get_conn(&self.manager.pool)?.insert_attempts(&attempts)?;
             }
         }
 
@@ -301,27 +624,68 @@ impl<'a> PulseRunner<'a> {
         let mut failed = vec![];
 
         for ev in evs.iter_mut() {
-            ev.updated_at = chrono::Utc::now().naive_utc();
-
-            if failed_ids.contains(&ev.id) {
+            // Deliveries for targets that no longer apply (renamed, excluded,
+            // filtered) must not hold the event open.
+            let applicable = routing[&ev.id]
+                .keys()
+                .map(|name| (ev.id.clone(), (*name).clone()))
+                .collect::<Vec<_>>();
+            let states = applicable
+                .iter()
+                .filter_map(|key| deliveries.get(key))
+                .collect::<Vec<_>>();
+
+            // A held target waits for its circuit, whatever its delivery says
+            let retry_at = applicable
+                .iter()
+                .filter_map(|key| match held.get(key) {
+                    Some(at) => Some(*at),
+                    None => deliveries
+                        .get(key)
+                        .filter(|d| d.status == String::from(ProcessStatus::Retry))
+                        .map(|d| d.next_retry_at.unwrap_or(now)),
+                })
+                .min();
+            let settles_at = applicable
+                .iter()
+                .filter_map(|key| waiting.get(key))
+                .min()
+                .copied();
+            let any_failed = states
+                .iter()
+                .any(|d| d.status == String::from(ProcessStatus::Failed));
+            let failed_now = failed_ids.contains(&ev.id);
+
+            ev.updated_at = now;
+            ev.claimed_by = None;
+            ev.claimed_until = None;
+
+            if failed_now {
                 ev.failed_times += 1;
+            }
 
-                if ev.failed_times >= self.manager.settings.opts.max_retries {
-                    ev.process_status = ProcessStatus::Failed.into();
-                    ev.next_retry_at = None;
-                    failed.push(get_conn(&self.manager.pool)?.save_changes(ev)?);
-                } else {
-                    let next_retry = chrono::Utc::now().naive_utc()
-                        + chrono::Duration::seconds(2_i64.pow(ev.failed_times as u32 + 1));
-
-                    ev.process_status = ProcessStatus::Retry.into();
-                    ev.next_retry_at = Some(next_retry);
+            if let Some(retry_at) = retry_at {
+                ev.process_status = ProcessStatus::Retry.into();
+                ev.next_retry_at = Some(settles_at.map_or(retry_at, |at| at.min(retry_at)));
 
-                    retrying.push(get_conn(&self.manager.pool)?.save_changes(ev)?);
+                let saved = get_conn(&self.manager.pool)?.save_changes(ev)?;
+                // A target completing while another waits out its backoff is
+                // not worth a retry notification.
+                if failed_now {
+                    retrying.push(saved);
                 }
+            } else if let Some(settles_at) = settles_at {
+                // Still collecting its directory, which is not a retry
+                ev.next_retry_at = Some(settles_at);
+                get_conn(&self.manager.pool)?.save_changes(ev)?;
+            } else if any_failed {
+                ev.process_status = ProcessStatus::Failed.into();
+                ev.next_retry_at = None;
+                failed.push(get_conn(&self.manager.pool)?.save_changes(ev)?);
             } else {
                 ev.process_status = ProcessStatus::Complete.into();
-                ev.processed_at = Some(chrono::Utc::now().naive_utc());
+                ev.next_retry_at = None;
+                ev.processed_at = Some(now);
                 succeeded.push(get_conn(&self.manager.pool)?.save_changes(ev)?);
             }
         }
@@ -329,26 +693,213 @@ impl<'a> PulseRunner<'a> {
         Ok((succeeded, retrying, failed))
     }
 
-    fn cleanup(&self) -> anyhow::Result<()> {
-        let time_before_cleanup = chrono::Utc::now().naive_utc()
-            - chrono::Duration::days(self.manager.settings.opts.cleanup_days as i64);
+    /// Sends each group of events to `target`, using the group's rewrite in place of the target's own.
+    async fn dispatch_routed(
+        &self,
+        target: &Target,
+        mut groups: Vec<(Option<&Rewrite>, Vec<&ScanEvent>)>,
+    ) -> anyhow::Result<ProcessResult> {
+        if let [(None, evs)] = groups.as_slice() {
+            return self.dispatch(target, evs).await;
+        }
 
-        let delete_old_events = diesel::delete(
-            scan_events
-                .filter(
-                    (found_status.eq::<String>(FoundStatus::NotFound.into()))
-                        .or(process_status.eq::<String>(ProcessStatus::Failed.into())),
-                )
-                .filter(created_at.lt(time_before_cleanup)),
-        );
+        let mut result = ProcessResult::default();
+
+        // Groups are sent at the same time, so they share the target's timeout
+        let outcomes =
+            futures::future::join_all(groups.drain(..).map(|(rewrite, evs)| async move {
+                let outcome = match rewrite {
+                    Some(rewrite) => self.dispatch(&target.with_rewrite(rewrite), &evs).await,
+                    None => self.dispatch(target, &evs).await,
+                };
+
+                (evs, outcome)
+            }))
+            .await;
 
-        if let Err(e) = delete_old_events.execute(&mut get_conn(&self.manager.pool)?) {
-            error!("failed to delete old events: {:?}", e);
+        for (evs, outcome) in outcomes {
+            match outcome {
+                Ok(outcome) => result.extend(outcome),
+                Err(e) => {
+                    for ev in evs {
+                        result.fail(&ev.id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Sends `evs` to `target`, split into single events when the target limits
+    /// its concurrency.
+    async fn dispatch(&self, target: &Target, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let Some(limit) = target.concurrency() else {
+            return target.process(evs).await;
+        };
+
+        let mut result = ProcessResult::default();
+
+        let mut outcomes = futures::stream::iter(evs)
+            .map(|ev| async move { (ev, target.process(std::slice::from_ref(ev)).await) })
+            .buffer_unordered(limit.max(1));
+
+        while let Some((ev, outcome)) = outcomes.next().await {
+            match outcome {
+                Ok(outcome) => result.extend(outcome),
+                Err(e) => result.fail(&ev.id, &e),
+            }
         }
 
+        Ok(result)
+    }
+
+    /// Prunes events past their retention, archiving them first when configured.
+    ///
+    /// Runs every [`CLEANUP_INTERVAL`], deleting at most [`CLEANUP_BATCHES`] batches so a
+    /// large backlog doesn't hold up processing, the rest is left to the following passes.
+    pub fn cleanup(&mut self) -> anyhow::Result<()> {
+        if self
+            .last_cleanup
+            .is_some_and(|at| at.elapsed() < CLEANUP_INTERVAL)
+        {
+            return Ok(());
+        }
+
+        let opts = &self.manager.settings.opts;
+        let retention = &opts.retention;
+        let now = chrono::Utc::now().naive_utc();
+        let before = |days: u64| now - chrono::Duration::days(days as i64);
+
+        let expiry = Expiry {
+            complete: retention.complete.map(before),
+            failed: Some(before(retention.failed.unwrap_or(opts.cleanup_days))),
+            not_found: Some(before(retention.not_found.unwrap_or(opts.cleanup_days))),
+            // Without `check_path` most processed events are never marked found, so unless
+            // `complete` says otherwise they go after `cleanup_days` like they always have
+            processed_not_found: !opts.check_path && retention.complete.is_none(),
+        };
+        let batch_size = retention.batch_size.max(1);
+
+        let mut conn = get_conn(&self.manager.pool)?;
+        let mut total = 0;
+
+        for _ in 0..CLEANUP_BATCHES {
+            let pruned =
+                conn.prune_events(&expiry, batch_size, |events| match &retention.archive {
+                    Some(dir) => archive::append(dir, now.date(), events),
+                    None => Ok(()),
+                });
+
+            match pruned {
+                Ok(pruned) => {
+                    total += pruned;
+
+                    if (pruned as i64) < batch_size {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    error!("failed to delete old events: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        if total > 0 {
+            debug!("pruned {} old events", total);
+        }
+
+        self.last_cleanup = Some(std::time::Instant::now());
+
         Ok(())
     }
 
+    /// When the runner next has work to do, `None` when only the fallback poll is needed.
+    pub fn next_wake(&self) -> anyhow::Result<Option<chrono::NaiveDateTime>> {
+        if !self.anchors_available {
+            return Ok(None);
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let check_path = self.manager.settings.opts.check_path;
+        let queued = [
+            String::from(ProcessStatus::Pending),
+            String::from(ProcessStatus::Retry),
+        ];
+        let mut conn = get_conn(&self.manager.pool)?;
+
+        // A pass only takes a batch of events, so more may already be due
+        let due = scan_events
+            .filter(process_status.eq_any(&queued))
+            .filter(next_retry_at.is_null().or(next_retry_at.lt(now)))
+            .filter(can_process.lt(now))
+            .filter(claimed_until.is_null().or(claimed_until.lt(now)));
+        let due: i64 = if check_path {
+            due.filter(found_status.eq::<String>(FoundStatus::Found.into()))
+                .count()
+                .get_result(&mut conn)?
+        } else {
+            due.count().get_result(&mut conn)?
+        };
+
+        if due > 0 {
+            return Ok(Some(now));
+        }
+
+        let mut wakes = vec![
+            scan_events
+                .filter(process_status.eq_any(&queued))
+                .filter(can_process.gt(now))
+                .select(diesel::dsl::min(can_process))
+                .get_result::<Option<chrono::NaiveDateTime>>(&mut conn)?,
+            scan_events
+                .filter(process_status.eq_any(&queued))
+                .filter(next_retry_at.gt(now))
+                .select(diesel::dsl::min(next_retry_at))
+                .get_result::<Option<chrono::NaiveDateTime>>(&mut conn)?,
+            // Events another instance is dispatching can be taken over once its lease lapses
+            scan_events
+                .filter(process_status.eq_any(&queued))
+                .filter(claimed_until.gt(now))
+                .select(diesel::dsl::min(claimed_until))
+                .get_result::<Option<chrono::NaiveDateTime>>(&mut conn)?,
+        ];
+
+        if check_path {
+            // Files that have not shown up are checked less often the longer they are overdue
+            let youngest_missing = scan_events
+                .filter(found_status.ne::<String>(FoundStatus::Found.into()))
+                .filter(process_status.eq::<String>(ProcessStatus::Pending.into()))
+                .filter(can_process.le(now))
+                .select(diesel::dsl::max(can_process))
+                .get_result::<Option<chrono::NaiveDateTime>>(&mut conn)?;
+
+            wakes.push(youngest_missing.map(|at| {
+                let overdue = (now - at).num_seconds() / 10;
+                now + chrono::Duration::seconds(
+                    overdue.clamp(1, self.manager.settings.opts.poll_interval.max(1) as i64),
+                )
+            }));
+
+            if let Some(stability) = &self.manager.settings.opts.stability {
+                let unstable: i64 = scan_events
+                    .filter(found_status.eq::<String>(FoundStatus::Unstable.into()))
+                    .filter(process_status.eq::<String>(ProcessStatus::Pending.into()))
+                    .count()
+                    .get_result(&mut conn)?;
+
+                if unstable > 0 {
+                    wakes.push(Some(
+                        now + chrono::Duration::seconds(stability.interval.max(1) as i64),
+                    ));
+                }
+            }
+        }
+
+        Ok(wakes.into_iter().flatten().min())
+    }
+
     pub async fn run(&mut self) -> anyhow::Result<()> {
         let set_anchors_available = self
             .manager
@@ -366,6 +917,11 @@ impl<'a> PulseRunner<'a> {
             self.anchors_available = set_anchors_available;
         }
 
+        self.manager
+            .metrics
+            .anchors_available
+            .set(i64::from(self.anchors_available));
+
         if !self.anchors_available {
             return Ok(());
         }
@@ -46,6 +46,9 @@ pub struct App {
     /// Proxy IPs whose `X-Forwarded-For` we honor for the login throttle's
     /// client identification. Empty (default) = trust nothing, use `peer_addr`.
     pub trusted_proxies: Vec<IpAddr>,
+    /// Relay live UI updates between instances sharing a Postgres database
+    /// (default: false). Has no effect on SQLite.
+    pub shared_bus: bool,
 }
 
 impl Default for App {
@@ -59,6 +62,7 @@ impl Default for App {
             base_path: String::new(),
             secure_cookies: false,
             trusted_proxies: Vec::new(),
+            shared_bus: false,
         }
     }
 }
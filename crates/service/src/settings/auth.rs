@@ -11,6 +11,8 @@ pub struct Auth {
     pub username: String,
     /// Password for basic auth (default: password)
     pub password: String,
+    /// Whether `/metrics` can be scraped without credentials (default: false)
+    pub public_metrics: bool,
 }
 
 impl Default for Auth {
@@ -19,6 +21,7 @@ impl Default for Auth {
             enabled: true,
             username: "admin".to_string(),
             password: "password".to_string(),
+            public_metrics: false,
         }
     }
 }
@@ -1,12 +1,15 @@
 use anyhow::Context;
 use app::App;
 use auth::Auth;
+use autopulse_database::models::EventKind;
 use figment::{
     providers::{Env, Format, Json, Serialized, Toml, Yaml},
     Figment,
 };
 use opts::Opts;
+use routes::{Route, Routed};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::path::Path;
 use std::{collections::HashMap, path::PathBuf};
@@ -49,6 +52,9 @@ pub mod auth;
 ///   max_retries: 10
 ///   default_timer_wait: 300
 ///   cleanup_days: 7
+///   retention:
+///     complete: 30
+///     archive: /data/archive
 /// ```
 pub mod opts;
 
@@ -96,6 +102,11 @@ pub mod targets;
 /// [Webhooks](webhooks) for all webhooks
 pub mod webhooks;
 
+/// Routing rules between triggers and targets
+///
+/// See [`Settings::routes`]
+pub mod routes;
+
 #[doc(hidden)]
 pub fn default_triggers() -> HashMap<String, Trigger> {
     let mut triggers = HashMap::new();
@@ -127,6 +138,25 @@ pub struct Settings {
 
     pub webhooks: HashMap<String, Webhook>,
 
+    /// Rules picking the targets an event is sent to
+    ///
+    /// The first rule matching an event decides its targets, events no rule matches go to every target.
+    /// Trigger `excludes` and target filters still apply on top of the picked targets.
+    ///
+    /// Example:
+    /// ```yml
+    /// routes:
+    ///   - triggers: [sonarr]
+    ///     path: '^/media/(?P<library>tv|anime)/'
+    ///     targets: ["plex_{library}"]
+    ///   - kinds: [deleted]
+    ///     targets: [jellyfin]
+    ///     rewrite:
+    ///       from: /media
+    ///       to: /data
+    /// ```
+    pub routes: Vec<Route>,
+
     /// List of paths to anchor the service to
     ///
     /// This is useful to prevent the service notifying a target when the drive is not mounted or visible
@@ -203,6 +233,7 @@ impl Default for Settings {
             triggers: default_triggers(),
             targets: HashMap::new(),
             webhooks: HashMap::new(),
+            routes: vec![],
             anchors: vec![],
         }
     }
@@ -340,9 +371,81 @@ impl Settings {
     pub fn normalize(&mut self) -> anyhow::Result<()> {
         self.add_default_manual_trigger()?;
 
+        for (name, target) in &self.targets {
+            target
+                .validate()
+                .with_context(|| format!("invalid target '{name}'"))?;
+        }
+
+        for (name, trigger) in &self.triggers {
+            if let Trigger::Sweep(sweep) = trigger {
+                sweep
+                    .validate()
+                    .with_context(|| format!("invalid trigger '{name}'"))?;
+            }
+        }
+
+        targets::dispatch_stages(&self.targets)?;
+
+        for (i, route) in self.routes.iter().enumerate() {
+            route
+                .validate(&self.triggers, &self.targets)
+                .with_context(|| format!("invalid route #{}", i + 1))?;
+        }
+
         Ok(())
     }
 
+    /// Targets an event from `trigger` is sent to, keyed by name.
+    ///
+    /// `path` is the path after the trigger's rewrite, as stored on the event.
+    pub fn route(
+        &self,
+        trigger: &str,
+        kind: EventKind,
+        path: &str,
+    ) -> BTreeMap<&String, Routed<'_>> {
+        let excludes = self
+            .triggers
+            .get(trigger)
+            .map(|t| t.excludes().as_slice())
+            .unwrap_or_default();
+
+        let picked = self
+            .routes
+            .iter()
+            .enumerate()
+            .find_map(|(i, route)| Some((i, route, route.matches(trigger, kind, path)?)));
+
+        self.targets
+            .iter()
+            .filter(|(name, _)| !excludes.contains(name))
+            .filter_map(|(name, target)| {
+                let (route, rewrite) = match &picked {
+                    Some((i, route, names)) => {
+                        if !names.contains(name) {
+                            return None;
+                        }
+                        (Some(*i), route.rewrite.as_ref())
+                    }
+                    None => (None, None),
+                };
+
+                let path = target.target_path(path, rewrite);
+
+                target.filter().allows(&path).then_some((
+                    name,
+                    Routed {
+                        target,
+                        route,
+                        rewrite,
+                        path,
+                    },
+                ))
+            })
+            .collect()
+    }
+
     pub fn add_default_manual_trigger(&mut self) -> anyhow::Result<()> {
         if !self.triggers.contains_key("manual") {
             self.triggers.insert(
@@ -482,4 +585,45 @@ mod tests {
             Some(Trigger::Manual(_))
         ));
     }
+
+    fn settings_with_targets(targets: serde_json::Value) -> Settings {
+        serde_json::from_value(serde_json::json!({ "targets": targets }))
+            .expect("settings should load")
+    }
+
+    #[test]
+    fn dispatch_stages_orders_targets_after_their_prerequisites() {
+        let settings = settings_with_targets(serde_json::json!({
+            "plex": { "type": "command", "raw": "true", "after": ["rclone", "tdarr"] },
+            "rclone": { "type": "command", "raw": "true" },
+            "tdarr": { "type": "command", "raw": "true", "after": ["rclone"] },
+            "jellyfin": { "type": "command", "raw": "true" },
+        }));
+
+        let stages = targets::dispatch_stages(&settings.targets).expect("stages should resolve");
+
+        assert_eq!(
+            stages,
+            vec![vec!["jellyfin", "rclone"], vec!["tdarr"], vec!["plex"]]
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_unknown_and_cyclic_after() {
+        let mut unknown = settings_with_targets(serde_json::json!({
+            "plex": { "type": "command", "raw": "true", "after": ["rclone"] },
+        }));
+
+        let err = unknown.normalize().expect_err("unknown target should fail");
+        assert!(err.to_string().contains("unknown target 'rclone'"), "{err}");
+
+        let mut cyclic = settings_with_targets(serde_json::json!({
+            "a": { "type": "command", "raw": "true", "after": ["b"] },
+            "b": { "type": "command", "raw": "true", "after": ["a"] },
+            "c": { "type": "command", "raw": "true" },
+        }));
+
+        let err = cyclic.normalize().expect_err("cycle should fail");
+        assert!(err.to_string().contains("targets: a, b"), "{err}");
+    }
 }
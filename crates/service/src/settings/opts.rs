@@ -35,21 +35,84 @@ impl From<&LogRotation> for Rotation {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Stability {
+    /// Identical size and modified time observations in a row before a file counts as found (default: 3)
+    pub checks: u32,
+
+    /// Seconds between observations (default: 5)
+    pub interval: u64,
+
+    /// Also count a file as found once it has not been modified for this many seconds
+    pub quiet: Option<u64>,
+}
+
+impl Default for Stability {
+    fn default() -> Self {
+        Self {
+            checks: 3,
+            interval: 5,
+            quiet: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Retention {
+    /// Days to keep processed events, unset to keep them forever (default: unset)
+    pub complete: Option<u64>,
+
+    /// Days to keep events that ran out of retries (default: `cleanup_days`)
+    pub failed: Option<u64>,
+
+    /// Days to keep events whose file was never found, processed ones follow `complete` once it is set or `check_path` is on (default: `cleanup_days`)
+    pub not_found: Option<u64>,
+
+    /// Directory expired events are appended to as gzip-compressed NDJSON before they are deleted, one file per day (default: disabled)
+    pub archive: Option<PathBuf>,
+
+    /// Events deleted at a time (default: 500)
+    pub batch_size: i64,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            complete: None,
+            failed: None,
+            not_found: None,
+            archive: None,
+            batch_size: 500,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct Opts {
     /// Check if the path exists before processing (default: false)
     pub check_path: bool,
 
+    /// Wait for files to stop changing before they count as found, requires `check_path` (default: disabled)
+    pub stability: Option<Stability>,
+
+    /// Files hashed at once for `file_hash` checks (default: 2)
+    pub hash_concurrency: usize,
+
     /// Maximum retries before giving up (default: 5)
     pub max_retries: i32,
 
     /// Default timer wait time (default: 60)
     pub default_timer_wait: u64,
 
-    /// Cleanup not_found events older than x days (default: 10)
+    /// Cleanup not_found and failed events older than x days, `retention` can set them apart (default: 10)
     pub cleanup_days: u64,
 
+    /// How long events are kept for each status, and where they are archived
+    pub retention: Retention,
+
     /// Log file path
     pub log_file: Option<PathBuf>,
 
@@ -67,21 +130,44 @@ pub struct Opts {
 
     /// Interval in seconds between webhook batch sends (default: 10)
     pub webhook_interval: u64,
+
+    /// Seconds a target may spend on one batch before its events are retried, Subsonic targets also get their `scan_timeout`. A runner pass lasts at most as long as its slowest target, targets waiting on `after` that don't fit in it move to the next pass (default: 300)
+    pub target_timeout: u64,
+
+    /// Consecutive failed batches before a target is paused, 0 to never pause (default: 5)
+    pub circuit_threshold: u32,
+
+    /// Seconds a paused target waits before a single event is sent to probe it (default: 60)
+    pub circuit_cooldown: u64,
+
+    /// Longest the runner sleeps between passes, new events and due retries wake it sooner (default: 30)
+    pub poll_interval: u64,
+
+    /// Seconds an instance sharing a Postgres or MySQL database holds the events it is dispatching before another instance may take them over, renewed before each stage of `after` targets so it only has to outlast one stage, keep it above `target_timeout` and any Subsonic `scan_timeout` (default: 600)
+    pub claim_lease: u64,
 }
 
 impl Default for Opts {
     fn default() -> Self {
         Self {
             check_path: false,
+            stability: None,
+            hash_concurrency: 2,
             max_retries: 5,
             default_timer_wait: 60,
             cleanup_days: 10,
+            retention: Retention::default(),
             log_file: None,
             log_file_rollover: LogRotation::default(),
             log_file_max_files: 30,
             webhook_retries: 3,
             webhook_timeout: 10,
             webhook_interval: 10,
+            target_timeout: 300,
+            circuit_threshold: 5,
+            circuit_cooldown: 60,
+            poll_interval: 30,
+            claim_lease: 600,
         }
     }
 }
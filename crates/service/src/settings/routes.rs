@@ -0,0 +1,202 @@
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::Target;
+use autopulse_database::models::EventKind;
+use autopulse_utils::regex::{Captures, Regex};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Route {
+    /// Trigger names the rule matches (default: any trigger)
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    /// Event kinds the rule matches, any of `created`, `modified`, `deleted` or `renamed` (default: any kind)
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    /// Regex matched against the trigger-rewritten path (default: any path)
+    pub path: Option<RoutePath>,
+    /// Targets to send matching events to, `{name}` is replaced by the named capture of `path`
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Rewrite used by the targets in place of their own
+    pub rewrite: Option<Rewrite>,
+}
+
+#[derive(Clone)]
+pub struct RoutePath {
+    regex: Regex,
+    source: String,
+}
+
+impl<'de> Deserialize<'de> for RoutePath {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(d)?;
+        let regex = Regex::new(&source)
+            .map_err(|e| D::Error::custom(format!("invalid route path regex `{source}`: {e}")))?;
+
+        Ok(Self { regex, source })
+    }
+}
+
+impl Serialize for RoutePath {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.source.serialize(s)
+    }
+}
+
+/// `{name}` placeholders in a route target.
+fn placeholders(target: &str) -> Vec<&str> {
+    target
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .collect()
+}
+
+fn expand(target: &str, captures: &Captures) -> String {
+    placeholders(target)
+        .into_iter()
+        .fold(target.to_string(), |expanded, name| {
+            let value = captures.name(name).map_or("", |m| m.as_str());
+            expanded.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+impl Route {
+    /// Target names for the event, `None` when the rule does not match it.
+    pub fn matches(&self, trigger: &str, kind: EventKind, path: &str) -> Option<Vec<String>> {
+        if !self.triggers.is_empty() && !self.triggers.iter().any(|t| t == trigger) {
+            return None;
+        }
+
+        if !self.kinds.is_empty()
+            && !self
+                .kinds
+                .iter()
+                .any(|k| EventKind::from_str(k) == Ok(kind))
+        {
+            return None;
+        }
+
+        let captures = match &self.path {
+            Some(route_path) => Some(route_path.regex.captures(path)?),
+            None => None,
+        };
+
+        Some(
+            self.targets
+                .iter()
+                .map(|target| match &captures {
+                    Some(captures) => expand(target, captures),
+                    None => target.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Checks the rule against the configured triggers and targets.
+    pub fn validate<T>(
+        &self,
+        triggers: &HashMap<String, T>,
+        targets: &HashMap<String, Target>,
+    ) -> anyhow::Result<()> {
+        for trigger in &self.triggers {
+            if !triggers.contains_key(trigger) {
+                return Err(anyhow::anyhow!("unknown trigger '{trigger}'"));
+            }
+        }
+
+        for kind in &self.kinds {
+            if EventKind::from_str(kind).is_err() {
+                return Err(anyhow::anyhow!("unknown event kind '{kind}'"));
+            }
+        }
+
+        for target in &self.targets {
+            let names = placeholders(target);
+
+            if names.is_empty() {
+                if !targets.contains_key(target) {
+                    return Err(anyhow::anyhow!("unknown target '{target}'"));
+                }
+                continue;
+            }
+
+            for name in names {
+                let captured = self
+                    .path
+                    .as_ref()
+                    .is_some_and(|p| p.regex.capture_names().flatten().any(|n| n == name));
+
+                if !captured {
+                    return Err(anyhow::anyhow!(
+                        "target '{target}' uses '{{{name}}}' but `path` has no capture named '{name}'"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A target an event is routed to, see [`Settings::route`](super::Settings::route).
+pub struct Routed<'a> {
+    pub target: &'a Target,
+    /// Index of the route that picked the target, `None` when no route matched
+    pub route: Option<usize>,
+    /// Rewrite from that route, used in place of the target's own
+    pub rewrite: Option<&'a Rewrite>,
+    /// The path as the target sees it
+    pub path: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(value: serde_json::Value) -> Route {
+        serde_json::from_value(value).expect("route should deserialize")
+    }
+
+    #[test]
+    fn matches_expands_named_captures() {
+        let route = route(serde_json::json!({
+            "triggers": ["sonarr"],
+            "kinds": ["created", "renamed"],
+            "path": "^/media/(?P<library>tv|anime)/",
+            "targets": ["plex_{library}", "jellyfin"],
+        }));
+
+        assert_eq!(
+            route.matches("sonarr", EventKind::Created, "/media/anime/Show/ep.mkv"),
+            Some(vec!["plex_anime".to_string(), "jellyfin".to_string()])
+        );
+        assert_eq!(
+            route.matches("radarr", EventKind::Created, "/media/tv/Show/ep.mkv"),
+            None
+        );
+        assert_eq!(
+            route.matches("sonarr", EventKind::Deleted, "/media/tv/Show/ep.mkv"),
+            None
+        );
+        assert_eq!(
+            route.matches("sonarr", EventKind::Created, "/media/movies/Movie.mkv"),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_rejects_placeholders_without_a_capture() {
+        let route = route(serde_json::json!({
+            "path": "^/media/(?P<library>tv|anime)/",
+            "targets": ["plex_{kind}"],
+        }));
+
+        let err = route
+            .validate::<()>(&HashMap::new(), &HashMap::new())
+            .expect_err("missing capture should fail");
+        assert!(err.to_string().contains("no capture named 'kind'"), "{err}");
+    }
+}
@@ -1,7 +1,7 @@
 use super::{Request, RequestBuilderPerform};
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use autopulse_database::models::ScanEvent;
 use autopulse_utils::get_url;
 use reqwest::header;
@@ -21,6 +21,13 @@ pub struct Audiobookshelf {
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -122,14 +129,13 @@ impl Audiobookshelf {
 }
 
 impl TargetProcess for Audiobookshelf {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
-        let mut succeeded = Vec::new();
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
 
         let libraries = self.get_libraries().await?;
 
         if libraries.is_empty() {
-            error!("no libraries found");
-            return Ok(succeeded);
+            return Err(anyhow::anyhow!("no libraries found"));
         }
 
         for ev in evs {
@@ -137,19 +143,23 @@ impl TargetProcess for Audiobookshelf {
                 Ok(Some(library_id)) => {
                     if let Err(e) = self.scan(ev, library_id).await {
                         error!("failed to scan audiobookshelf: {}", e);
+                        result.fail(&ev.id, &e);
                     } else {
-                        succeeded.push(ev.id.clone());
+                        result.succeed(&ev.id);
                     }
                 }
                 Ok(None) => {
-                    error!("no library found for {}", ev.get_path(&self.rewrite));
+                    let e = anyhow::anyhow!("no library found for {}", ev.get_path(&self.rewrite));
+                    error!("{e}");
+                    result.fail(&ev.id, &e);
                 }
                 Err(e) => {
                     error!("failed to choose library: {}", e);
+                    result.fail(&ev.id, &e);
                 }
             }
         }
 
-        Ok(succeeded)
+        Ok(result)
     }
 }
@@ -1,7 +1,10 @@
 use super::{Request, RequestBuilderPerform};
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::{auth::Auth, targets::TargetProcess};
+use crate::settings::{
+    auth::Auth,
+    targets::{ProcessResult, TargetProcess},
+};
 use autopulse_database::models::ScanEvent;
 use autopulse_utils::get_url;
 use reqwest::header;
@@ -21,6 +24,13 @@ pub struct Autopulse {
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -57,21 +67,22 @@ impl Autopulse {
 }
 
 impl TargetProcess for Autopulse {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
-        let mut succeeded = Vec::new();
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
 
         for ev in evs {
             match self.scan(ev).await {
                 Ok(()) => {
-                    succeeded.push(ev.id.clone());
+                    result.succeed(&ev.id);
                     debug!("file scanned: {}", ev.get_path(&self.rewrite));
                 }
                 Err(e) => {
                     error!("error scanning file: {}", e);
+                    result.fail(&ev.id, &e);
                 }
             }
         }
 
-        Ok(succeeded)
+        Ok(result)
     }
 }
@@ -1,6 +1,6 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use autopulse_database::models::ScanEvent;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error};
@@ -20,13 +20,23 @@ pub struct Command {
     pub timeout: Option<u64>,
     /// Raw command to run
     ///
-    /// Example: `echo $FILE_PATH >> list.log`
+    /// `FILE_PATH`, `EVENT_KIND` (`created`, `modified`, `deleted` or `renamed`) and,
+    /// for renames, `PREVIOUS_PATH` are set in the environment of both `path` and `raw` commands
+    ///
+    /// Example: `echo $EVENT_KIND $FILE_PATH >> list.log`
     pub raw: Option<String>,
     /// Rewrite path for the file
     pub rewrite: Option<Rewrite>,
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
 }
 
 impl Command {
@@ -40,11 +50,27 @@ impl Command {
         }
 
         let ev_path = ev.get_path(&self.rewrite);
+        let previous_path = ev.previous_path.clone().map(|path| match &self.rewrite {
+            Some(rewrite) => rewrite.rewrite_path(path),
+            None => path,
+        });
+
+        let command = |program: &str| {
+            let mut command = tokio::process::Command::new(program);
+
+            command
+                .env("FILE_PATH", &ev_path)
+                .env("EVENT_KIND", &ev.event_kind);
+
+            if let Some(previous_path) = &previous_path {
+                command.env("PREVIOUS_PATH", previous_path);
+            }
+
+            command
+        };
 
         if let Some(path) = self.path.clone() {
-            let output = tokio::process::Command::new(path.clone())
-                .arg(&ev_path)
-                .output();
+            let output = command(&path).arg(&ev_path).output();
 
             let timeout = self.timeout.unwrap_or(10);
 
@@ -63,11 +89,7 @@ impl Command {
         }
 
         if let Some(raw) = self.raw.clone() {
-            let output = tokio::process::Command::new("sh")
-                .env("FILE_PATH", &ev_path)
-                .arg("-c")
-                .arg(&raw)
-                .output();
+            let output = command("sh").arg("-c").arg(&raw).output();
 
             let timeout = self.timeout.unwrap_or(10);
 
@@ -90,18 +112,19 @@ impl Command {
 }
 
 impl TargetProcess for Command {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
-        let mut succeeded = Vec::new();
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
 
         for ev in evs {
             if let Err(e) = self.run(ev).await {
                 error!("failed to process '{}': {}", ev.get_path(&self.rewrite), e);
+                result.fail(&ev.id, &e);
             } else {
-                succeeded.push(ev.id.clone());
+                result.succeed(&ev.id);
             }
         }
 
-        Ok(succeeded)
+        Ok(result)
     }
 }
 
@@ -123,15 +146,48 @@ mod tests {
             found_status: "found".to_string(),
             failed_times: 0,
             next_retry_at: None,
-            targets_hit: String::new(),
             found_at: None,
             processed_at: None,
             created_at: now,
             updated_at: now,
             can_process: now,
+            event_kind: "modified".to_string(),
+            previous_path: None,
+            claimed_by: None,
+            claimed_until: None,
         }
     }
 
+    fn raw(raw: &str) -> Command {
+        Command {
+            path: None,
+            timeout: None,
+            raw: Some(raw.to_string()),
+            rewrite: None,
+            filter: Default::default(),
+            concurrency: None,
+            after: vec![],
+            coalesce: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn run_exposes_event_kind_and_previous_path() {
+        let mut ev = scan_event();
+        ev.event_kind = "renamed".to_string();
+        ev.previous_path = Some("/media/old.mkv".to_string());
+
+        raw(r#"[ "$EVENT_KIND" = renamed ] && [ "$PREVIOUS_PATH" = /media/old.mkv ]"#)
+            .run(&ev)
+            .await
+            .expect("command should see the event kind and previous path");
+
+        raw(r#"[ "$EVENT_KIND" = deleted ]"#)
+            .run(&scan_event())
+            .await
+            .expect_err("a modified event is not a delete");
+    }
+
     #[tokio::test]
     async fn run_rejects_command_without_path_or_raw() {
         let command = Command {
@@ -140,6 +196,9 @@ mod tests {
             raw: None,
             rewrite: None,
             filter: Default::default(),
+            concurrency: None,
+            after: vec![],
+            coalesce: None,
         };
 
         let err = command
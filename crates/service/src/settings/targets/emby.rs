@@ -1,9 +1,9 @@
 use super::{Request, RequestBuilderPerform};
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use anyhow::Context;
-use autopulse_database::models::ScanEvent;
+use autopulse_database::models::{EventKind, ScanEvent};
 use autopulse_utils::{get_url, RuntimePath};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
@@ -37,6 +37,13 @@ pub struct Emby {
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -104,6 +111,15 @@ struct UpdateRequest {
     update_type: String,
 }
 
+/// The `UpdateType` Jellyfin/Emby expects for an [`EventKind`].
+fn update_type(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Created => "Created",
+        EventKind::Deleted => "Deleted",
+        EventKind::Modified | EventKind::Renamed => "Modified",
+    }
+}
+
 #[derive(Serialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 #[doc(hidden)]
@@ -331,7 +347,7 @@ impl Emby {
             .iter()
             .map(|ev| UpdateRequest {
                 path: ev.get_path(&self.rewrite),
-                update_type: "Modified".to_string(),
+                update_type: update_type(ev.kind()).to_string(),
             })
             .collect();
 
@@ -371,13 +387,13 @@ impl Emby {
 }
 
 impl TargetProcess for Emby {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
         let libraries = self
             .libraries()
             .await
             .context("failed to fetch libraries")?;
 
-        let mut succeeded: HashMap<String, bool> = HashMap::new();
+        let mut result = ProcessResult::default();
 
         let mut to_find = HashMap::new();
         let mut to_refresh = Vec::new();
@@ -385,6 +401,12 @@ impl TargetProcess for Emby {
 
         if self.refresh_metadata {
             for ev in evs {
+                // A deleted file has no item left to refresh
+                if ev.kind() == EventKind::Deleted {
+                    to_scan.push(*ev);
+                    continue;
+                }
+
                 let ev_path = ev.get_path(&self.rewrite);
 
                 let matched_libraries = self.get_libraries(&libraries, &ev_path);
@@ -397,6 +419,10 @@ impl TargetProcess for Emby {
                     error!(
                         "failed to find library for file '{ev_path}'. Known locations: {known:?}"
                     );
+                    result.fail(
+                        &ev.id,
+                        &anyhow::anyhow!("failed to find library for file '{ev_path}'"),
+                    );
                     continue;
                 }
 
@@ -424,11 +450,11 @@ impl TargetProcess for Emby {
                 match self.refresh_item(&item).await {
                     Ok(()) => {
                         debug!("refreshed item: {}", item.id);
-                        *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                        result.succeed(&ev.id);
                     }
                     Err(e) => {
                         error!("failed to refresh item: {}", e);
-                        succeeded.insert(ev.id.clone(), false);
+                        result.fail(&ev.id, &e);
                     }
                 }
             }
@@ -442,23 +468,20 @@ impl TargetProcess for Emby {
                     for ev in &to_scan {
                         debug!("scanned file: {}", ev.file_path);
 
-                        *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                        result.succeed(&ev.id);
                     }
                 }
                 Err(e) => {
                     error!("failed to scan items: {}", e);
 
                     for ev in &to_scan {
-                        succeeded.insert(ev.id.clone(), false);
+                        result.fail(&ev.id, &e);
                     }
                 }
             }
         }
 
-        Ok(succeeded
-            .iter()
-            .filter_map(|(k, v)| if *v { Some(k.clone()) } else { None })
-            .collect())
+        Ok(result)
     }
 }
 
@@ -466,6 +489,14 @@ impl TargetProcess for Emby {
 mod tests {
     use super::*;
 
+    #[test]
+    fn update_type_follows_event_kind() {
+        assert_eq!(update_type(EventKind::Created), "Created");
+        assert_eq!(update_type(EventKind::Modified), "Modified");
+        assert_eq!(update_type(EventKind::Renamed), "Modified");
+        assert_eq!(update_type(EventKind::Deleted), "Deleted");
+    }
+
     fn lib(name: &str, paths: &[&str]) -> Library {
         Library {
             name: name.to_string(),
@@ -483,6 +514,9 @@ mod tests {
             refresh_metadata: true,
             rewrite: None,
             filter: PathFilter::default(),
+            concurrency: None,
+            after: vec![],
+            coalesce: None,
             request: Request::default(),
             path_match: PathMatch::default(),
         }
@@ -1,7 +1,7 @@
 use super::{Request, RequestBuilderPerform};
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use anyhow::Context;
 use autopulse_database::models::ScanEvent;
 use autopulse_utils::{get_url, RuntimePath};
@@ -20,6 +20,13 @@ pub struct FileFlows {
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -213,8 +220,8 @@ impl FileFlows {
 }
 
 impl TargetProcess for FileFlows {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
-        let mut succeeded = Vec::new();
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
         let libraries = self
             .get_libraries()
             .await
@@ -243,6 +250,11 @@ impl TargetProcess for FileFlows {
                     library.uid,
                     files.len()
                 );
+
+                let e = anyhow::anyhow!("library '{}' is disabled", library.uid);
+                for ev in files {
+                    result.fail(&ev.id, &e);
+                }
                 continue;
             }
 
@@ -255,7 +267,7 @@ impl TargetProcess for FileFlows {
             for ev in evs {
                 let event_path = ev.get_path(&self.rewrite);
                 if RuntimePath::new(&event_path).is_directory() {
-                    succeeded.push(ev.id.clone());
+                    result.succeed(&ev.id);
                     continue;
                 }
 
@@ -293,10 +305,16 @@ impl TargetProcess for FileFlows {
                     Ok(()) => {
                         for (ev, _) in &processed {
                             debug!("reprocessed file: {}", ev.get_path(&self.rewrite));
+                            result.succeed(&ev.id);
+                        }
+                    }
+                    Err(e) => {
+                        error!("failed to reprocess files: {}", e);
+
+                        for (ev, _) in &processed {
+                            result.fail(&ev.id, &e);
                         }
-                        succeeded.extend(processed.iter().map(|(ev, _)| ev.id.clone()));
                     }
-                    Err(e) => error!("failed to reprocess files: {}", e),
                 }
             }
 
@@ -311,15 +329,21 @@ impl TargetProcess for FileFlows {
                     Ok(()) => {
                         for (ev, _) in &not_processed {
                             debug!("manually added file: {}", ev.get_path(&self.rewrite));
+                            result.succeed(&ev.id);
+                        }
+                    }
+                    Err(e) => {
+                        error!("failed to manually add files: {}", e);
+
+                        for (ev, _) in &not_processed {
+                            result.fail(&ev.id, &e);
                         }
-                        succeeded.extend(not_processed.iter().map(|(ev, _)| ev.id.clone()));
                     }
-                    Err(e) => error!("failed to manually add files: {}", e),
                 }
             }
         }
 
-        Ok(succeeded)
+        Ok(result)
     }
 }
 
@@ -0,0 +1,579 @@
+use super::{Request, RequestBuilderPerform};
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, error};
+
+#[doc(hidden)]
+fn default_method() -> Template {
+    Template::parse("POST").expect("static template should parse")
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Http {
+    /// URL to send the request to
+    pub url: Template,
+    /// HTTP method (default: POST)
+    #[serde(default = "default_method")]
+    pub method: Template,
+    /// Headers added to each request
+    #[serde(default)]
+    pub headers: HashMap<String, Template>,
+    /// Request body
+    pub body: Option<Template>,
+    /// Whether to send one request per batch instead of one per event (default: false)
+    ///
+    /// Only `events` and `count` are available to templates in batch mode
+    #[serde(default)]
+    pub batch: bool,
+    /// How to decide whether a request succeeded
+    #[serde(default)]
+    pub success: HttpSuccess,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct HttpSuccess {
+    /// Status codes that count as success (default: any 2xx)
+    ///
+    /// Responses outside 2xx are always failures
+    pub status: Option<Vec<u16>>,
+    /// [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901) into the response body that must be present, e.g. `/result/ok`
+    pub pointer: Option<String>,
+    /// Value the `pointer` must equal (default: anything but `null` or `false`)
+    pub equals: Option<serde_json::Value>,
+}
+
+/// A string with `{{ variable }}` or `{{ variable | filter }}` placeholders.
+///
+/// # Variables
+///
+/// - `path`: path of the file as received by the trigger
+/// - `rewritten_path`: path of the file after the target `rewrite`
+/// - `hash`: hash of the file, empty when unknown
+/// - `trigger`: name of the trigger that created the event
+/// - `event_id`: id of the event
+/// - `event_kind`: `created`, `modified`, `deleted` or `renamed`
+/// - `previous_path`: rewritten path before a rename, empty otherwise
+/// - `events`: JSON array of objects with all of the above
+/// - `count`: number of events in the request
+///
+/// # Filters
+///
+/// - `json`: value as JSON, so strings are quoted and escaped
+/// - `url`: value percent-encoded for use in a URL
+///
+/// ```yml
+/// url: "http://localhost:8080/scan?path={{ rewritten_path | url }}"
+/// body: '{"path": {{ rewritten_path | json }}, "kind": "{{ event_kind }}"}'
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    Variable(Variable, Option<Filter>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Variable {
+    Path,
+    RewrittenPath,
+    Hash,
+    Trigger,
+    EventId,
+    EventKind,
+    PreviousPath,
+    Events,
+    Count,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Filter {
+    Json,
+    Url,
+}
+
+impl Variable {
+    const fn per_event(self) -> bool {
+        !matches!(self, Self::Events | Self::Count)
+    }
+
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Path => "path",
+            Self::RewrittenPath => "rewritten_path",
+            Self::Hash => "hash",
+            Self::Trigger => "trigger",
+            Self::EventId => "event_id",
+            Self::EventKind => "event_kind",
+            Self::PreviousPath => "previous_path",
+            Self::Events => "events",
+            Self::Count => "count",
+        }
+    }
+}
+
+impl std::str::FromStr for Variable {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Path,
+            Self::RewrittenPath,
+            Self::Hash,
+            Self::Trigger,
+            Self::EventId,
+            Self::EventKind,
+            Self::PreviousPath,
+            Self::Events,
+            Self::Count,
+        ]
+        .into_iter()
+        .find(|variable| variable.key() == s)
+        .ok_or_else(|| anyhow::anyhow!("unknown template variable '{s}'"))
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "url" => Ok(Self::Url),
+            _ => Err(anyhow::anyhow!("unknown template filter '{s}'")),
+        }
+    }
+}
+
+/// Percent-encodes everything but the RFC 3986 unreserved characters.
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+impl Template {
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let mut segments = vec![];
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(anyhow::anyhow!("unclosed '{{{{' in template '{source}'"));
+            };
+
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+
+            let mut parts = rest[start + 2..start + end].split('|').map(str::trim);
+            let variable = parts.next().unwrap_or_default().parse()?;
+            let filter = parts.next().map(str::parse).transpose()?;
+
+            if parts.next().is_some() {
+                return Err(anyhow::anyhow!(
+                    "only one filter is allowed per placeholder in template '{source}'"
+                ));
+            }
+
+            segments.push(Segment::Variable(variable, filter));
+            rest = &rest[start + end + 2..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    fn variables(&self) -> impl Iterator<Item = Variable> + '_ {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Variable(variable, _) => Some(*variable),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    fn render(&self, context: &Context) -> anyhow::Result<String> {
+        let mut rendered = String::with_capacity(self.source.len());
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => rendered.push_str(literal),
+                Segment::Variable(variable, filter) => {
+                    let value = context.get(*variable).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "'{}' is only available per event, not in batch mode",
+                            variable.key()
+                        )
+                    })?;
+
+                    let plain = match &value {
+                        serde_json::Value::String(s) => s.clone(),
+                        serde_json::Value::Null => String::new(),
+                        other => other.to_string(),
+                    };
+
+                    match filter {
+                        None => rendered.push_str(&plain),
+                        Some(Filter::Json) => rendered.push_str(&value.to_string()),
+                        Some(Filter::Url) => rendered.push_str(&url_encode(&plain)),
+                    }
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = anyhow::Error;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<Template> for String {
+    fn from(template: Template) -> Self {
+        template.source
+    }
+}
+
+#[doc(hidden)]
+struct Context {
+    /// Variables of the event when rendering per event
+    event: Option<serde_json::Map<String, serde_json::Value>>,
+    events: Vec<serde_json::Value>,
+}
+
+impl Context {
+    fn get(&self, variable: Variable) -> Option<serde_json::Value> {
+        match variable {
+            Variable::Events => Some(serde_json::Value::Array(self.events.clone())),
+            Variable::Count => Some(self.events.len().into()),
+            _ => self.event.as_ref()?.get(variable.key()).cloned(),
+        }
+    }
+}
+
+impl Http {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        self.request
+            .client_builder(header::HeaderMap::new())
+            .build()
+            .map_err(Into::into)
+    }
+
+    fn templates(&self) -> impl Iterator<Item = &Template> {
+        [&self.url, &self.method]
+            .into_iter()
+            .chain(self.headers.values())
+            .chain(self.body.as_ref())
+    }
+
+    /// Rejects per-event variables in batch mode when the config is loaded.
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if !self.batch {
+            return Ok(());
+        }
+
+        match self
+            .templates()
+            .flat_map(Template::variables)
+            .find(|variable| variable.per_event())
+        {
+            Some(variable) => Err(anyhow::anyhow!(
+                "'{}' is only available per event, use 'events' in batch mode",
+                variable.key()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn event_variables(&self, ev: &ScanEvent) -> serde_json::Map<String, serde_json::Value> {
+        let previous_path = ev.previous_path.clone().map(|path| match &self.rewrite {
+            Some(rewrite) => rewrite.rewrite_path(path),
+            None => path,
+        });
+
+        let mut variables = serde_json::Map::new();
+
+        variables.insert(Variable::Path.key().into(), ev.file_path.clone().into());
+        variables.insert(
+            Variable::RewrittenPath.key().into(),
+            ev.get_path(&self.rewrite).into(),
+        );
+        variables.insert(Variable::Hash.key().into(), ev.file_hash.clone().into());
+        variables.insert(
+            Variable::Trigger.key().into(),
+            ev.event_source.clone().into(),
+        );
+        variables.insert(Variable::EventId.key().into(), ev.id.clone().into());
+        variables.insert(
+            Variable::EventKind.key().into(),
+            ev.event_kind.clone().into(),
+        );
+        variables.insert(Variable::PreviousPath.key().into(), previous_path.into());
+
+        variables
+    }
+
+    fn context(&self, evs: &[&ScanEvent]) -> Context {
+        let events = evs
+            .iter()
+            .map(|ev| serde_json::Value::Object(self.event_variables(ev)))
+            .collect();
+
+        let event = match (self.batch, evs) {
+            (false, [ev]) => Some(self.event_variables(ev)),
+            _ => None,
+        };
+
+        Context { event, events }
+    }
+
+    fn check_body(&self, body: &str) -> anyhow::Result<()> {
+        let Some(pointer) = &self.success.pointer else {
+            return Ok(());
+        };
+
+        let body: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| anyhow::anyhow!("response is not valid JSON: {e}"))?;
+        let value = body.pointer(pointer);
+
+        let ok = match (&self.success.equals, value) {
+            (Some(expected), Some(value)) => value == expected,
+            (None, Some(value)) => !matches!(
+                value,
+                serde_json::Value::Null | serde_json::Value::Bool(false)
+            ),
+            (_, None) => false,
+        };
+
+        if ok {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "response value at '{}' was {}",
+                pointer,
+                value.map_or_else(|| "missing".to_string(), ToString::to_string)
+            ))
+        }
+    }
+
+    async fn send(&self, evs: &[&ScanEvent]) -> anyhow::Result<()> {
+        let context = self.context(evs);
+
+        let method =
+            reqwest::Method::from_bytes(self.method.render(&context)?.to_uppercase().as_bytes())?;
+        let url = self.url.render(&context)?;
+
+        let mut request = self.get_client()?.request(method, &url);
+
+        for (name, value) in &self.headers {
+            request = request.header(name, value.render(&context)?);
+        }
+
+        if let Some(body) = &self.body {
+            request = request.body(body.render(&context)?);
+        }
+
+        let res = request.perform().await?;
+        let status = res.status();
+
+        if let Some(allowed) = &self.success.status {
+            if !allowed.contains(&status.as_u16()) {
+                return Err(anyhow::anyhow!("unexpected status {} from {}", status, url));
+            }
+        }
+
+        self.check_body(&res.text().await?)
+    }
+}
+
+impl TargetProcess for Http {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+
+        let batches: Vec<&[&ScanEvent]> = if self.batch {
+            vec![evs]
+        } else {
+            evs.chunks(1).collect()
+        };
+
+        for batch in batches {
+            match self.send(batch).await {
+                Ok(()) => {
+                    debug!("sent request for {} event(s)", batch.len());
+
+                    for ev in batch {
+                        result.succeed(&ev.id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to send request: {}", e);
+
+                    for ev in batch {
+                        result.fail(&ev.id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Http, Template};
+    use autopulse_database::models::ScanEvent;
+
+    fn scan_event() -> ScanEvent {
+        let now = chrono::Utc::now().naive_utc();
+
+        ScanEvent {
+            id: "event-id".to_string(),
+            event_source: "sonarr".to_string(),
+            event_timestamp: now,
+            file_path: "/downloads/Show/S01E01 & more.mkv".to_string(),
+            file_hash: None,
+            process_status: "pending".to_string(),
+            found_status: "found".to_string(),
+            failed_times: 0,
+            next_retry_at: None,
+            found_at: None,
+            processed_at: None,
+            created_at: now,
+            updated_at: now,
+            can_process: now,
+            event_kind: "created".to_string(),
+            previous_path: None,
+            claimed_by: None,
+            claimed_until: None,
+        }
+    }
+
+    fn http(config: serde_json::Value) -> Http {
+        serde_json::from_value(config).expect("http target should deserialize")
+    }
+
+    #[test]
+    fn renders_event_variables_with_filters() {
+        let target = http(serde_json::json!({
+            "url": "http://localhost/scan?path={{ rewritten_path | url }}&trigger={{trigger}}",
+            "rewrite": { "from": "^/downloads", "to": "/media" },
+        }));
+        let ev = scan_event();
+        let context = target.context(&[&ev]);
+
+        assert_eq!(
+            target.url.render(&context).unwrap(),
+            "http://localhost/scan?path=%2Fmedia%2FShow%2FS01E01%20%26%20more.mkv&trigger=sonarr"
+        );
+
+        let body = Template::parse(
+            r#"{"path": {{ path | json }}, "hash": {{hash|json}}, "kind": "{{ event_kind }}"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            body.render(&context).unwrap(),
+            r#"{"path": "/downloads/Show/S01E01 & more.mkv", "hash": null, "kind": "created"}"#
+        );
+    }
+
+    #[test]
+    fn renders_events_in_batch_mode() {
+        let target = http(serde_json::json!({
+            "url": "http://localhost/batch",
+            "body": r#"{"count": {{ count }}, "events": {{ events }}}"#,
+            "batch": true,
+        }));
+        let ev = scan_event();
+        let rendered = target
+            .body
+            .as_ref()
+            .unwrap()
+            .render(&target.context(&[&ev, &ev]))
+            .unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["events"][1]["event_id"], "event-id");
+        assert!(target.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_variables_and_per_event_variables_in_batch_mode() {
+        assert!(Template::parse("{{ nope }}").is_err());
+        assert!(Template::parse("{{ path | nope }}").is_err());
+        assert!(Template::parse("{{ path").is_err());
+
+        let target = http(serde_json::json!({
+            "url": "http://localhost/{{ path | url }}",
+            "batch": true,
+        }));
+
+        assert!(target.validate().is_err());
+    }
+
+    #[test]
+    fn checks_the_json_pointer_in_the_response() {
+        let target = http(serde_json::json!({
+            "url": "http://localhost",
+            "success": { "pointer": "/result/status", "equals": "ok" },
+        }));
+
+        assert!(target.check_body(r#"{"result": {"status": "ok"}}"#).is_ok());
+        assert!(target
+            .check_body(r#"{"result": {"status": "error"}}"#)
+            .is_err());
+        assert!(target.check_body(r#"{"result": {}}"#).is_err());
+
+        let truthy = http(serde_json::json!({
+            "url": "http://localhost",
+            "success": { "pointer": "/queued" },
+        }));
+
+        assert!(truthy.check_body(r#"{"queued": 3}"#).is_ok());
+        assert!(truthy.check_body(r#"{"queued": false}"#).is_err());
+    }
+}
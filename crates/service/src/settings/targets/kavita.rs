@@ -0,0 +1,219 @@
+use super::{Request, RequestBuilderPerform};
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use autopulse_utils::{get_url, RuntimePath};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{debug, error};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Kavita {
+    /// URL to the Kavita server
+    pub url: String,
+    /// API key of an admin user on the Kavita server
+    pub token: String,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+struct AuthenticateResponse {
+    token: String,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize, Clone, Debug)]
+struct Library {
+    name: String,
+    folders: Vec<String>,
+}
+
+/// Library with the most specific folder matching `path`.
+fn get_library<'a>(libraries: &'a [Library], path: &str) -> Option<&'a Library> {
+    let event_path = RuntimePath::new(path);
+    let mut matches: Vec<(usize, &Library)> = vec![];
+
+    for library in libraries {
+        for folder in &library.folders {
+            let folder_path = RuntimePath::new(folder);
+            if event_path.starts_with(folder_path) {
+                matches.push((folder_path.component_count(), library));
+            }
+        }
+    }
+
+    matches
+        .into_iter()
+        .max_by_key(|(components, _)| *components)
+        .map(|(_, library)| library)
+}
+
+impl Kavita {
+    fn get_client(&self, jwt: Option<&str>) -> anyhow::Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+
+        if let Some(jwt) = jwt {
+            headers.insert("Authorization", format!("Bearer {jwt}").parse()?);
+        }
+
+        self.request
+            .client_builder(headers)
+            .build()
+            .map_err(Into::into)
+    }
+
+    /// Exchanges the API key for a session token.
+    ///
+    /// Kavita only takes the key in the query, so the URL is kept out of any error to not store it.
+    async fn authenticate(&self) -> anyhow::Result<String> {
+        let client = self.get_client(None)?;
+        let mut url = get_url(&self.url)?.join("api/Plugin/authenticate")?;
+
+        url.query_pairs_mut()
+            .append_pair("apiKey", &self.token)
+            .append_pair("pluginName", "autopulse");
+
+        let res: AuthenticateResponse = client
+            .post(url)
+            .perform()
+            .await?
+            .json()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+
+        Ok(res.token)
+    }
+
+    async fn libraries(&self) -> anyhow::Result<Vec<Library>> {
+        let jwt = self.authenticate().await?;
+        let client = self.get_client(Some(&jwt))?;
+        let url = get_url(&self.url)?.join("api/Library/libraries")?;
+
+        let res = client.get(url).perform().await?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Scans the series in `folder`, Kavita falls back to a library scan when none is found.
+    async fn scan(&self, folder: &str) -> anyhow::Result<()> {
+        let client = self.get_client(None)?;
+        let url = get_url(&self.url)?.join("api/Library/scan-folder")?;
+
+        client
+            .post(url)
+            .json(&serde_json::json!({
+                "apiKey": self.token,
+                "folderPath": folder,
+            }))
+            .perform()
+            .await
+            .map(|_| ())
+    }
+}
+
+impl TargetProcess for Kavita {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+
+        let libraries = self.libraries().await?;
+
+        if libraries.is_empty() {
+            return Err(anyhow::anyhow!("no libraries found"));
+        }
+
+        let mut folders: BTreeMap<String, Vec<&ScanEvent>> = BTreeMap::new();
+
+        for ev in evs {
+            let ev_path = ev.get_path(&self.rewrite);
+
+            match get_library(&libraries, &ev_path) {
+                Some(library) => {
+                    debug!("found library '{}' for {}", library.name, ev_path);
+
+                    folders
+                        .entry(
+                            RuntimePath::new(&ev_path)
+                                .parent_or_self()
+                                .as_str()
+                                .to_string(),
+                        )
+                        .or_default()
+                        .push(ev);
+                }
+                None => {
+                    let e = anyhow::anyhow!("no library found for {}", ev_path);
+                    error!("{e}");
+                    result.fail(&ev.id, &e);
+                }
+            }
+        }
+
+        for (folder, evs) in folders {
+            match self.scan(&folder).await {
+                Ok(()) => {
+                    debug!("scanned folder '{}'", folder);
+
+                    for ev in evs {
+                        result.succeed(&ev.id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to scan folder '{}': {}", folder, e);
+
+                    for ev in evs {
+                        result.fail(&ev.id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_library, Library};
+
+    #[test]
+    fn get_library_matches_any_folder_and_prefers_the_most_specific() {
+        let libraries = vec![
+            Library {
+                name: "Books".to_string(),
+                folders: vec!["/data/ebooks".to_string(), "/mnt/books".to_string()],
+            },
+            Library {
+                name: "Light Novels".to_string(),
+                folders: vec!["/data/ebooks/light-novels".to_string()],
+            },
+        ];
+
+        assert_eq!(
+            get_library(&libraries, "/mnt/books/Author/Book.epub").map(|l| l.name.as_str()),
+            Some("Books")
+        );
+        assert_eq!(
+            get_library(&libraries, "/data/ebooks/light-novels/Series/v01.epub")
+                .map(|l| l.name.as_str()),
+            Some("Light Novels")
+        );
+        assert!(get_library(&libraries, "/data/comics/Series/001.cbz").is_none());
+    }
+}
@@ -0,0 +1,229 @@
+use super::{Request, RequestBuilderPerform};
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::{EventKind, ScanEvent};
+use autopulse_utils::{get_url, RuntimePath, RuntimePathFlavor};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{debug, error};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Kodi {
+    /// URL to the Kodi web server
+    pub url: String,
+    /// Username for the Kodi web server
+    pub username: Option<String>,
+    /// Password for the Kodi web server
+    pub password: Option<String>,
+    /// Library to scan (default: video)
+    #[serde(default)]
+    pub library: KodiLibrary,
+    /// Whether to clean the library after a file is deleted (default: false)
+    #[serde(default)]
+    pub clean: bool,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+/// Kodi library to scan
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KodiLibrary {
+    /// `VideoLibrary`
+    #[default]
+    Video,
+    /// `AudioLibrary`
+    Audio,
+}
+
+impl KodiLibrary {
+    const fn namespace(self) -> &'static str {
+        match self {
+            Self::Video => "VideoLibrary",
+            Self::Audio => "AudioLibrary",
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+struct RpcResponse {
+    error: Option<RpcError>,
+}
+
+/// Kodi only matches a scan directory against its sources with a trailing separator.
+fn scan_directory(path: &str) -> String {
+    let directory = RuntimePath::new(path).parent_or_self();
+    let separator = match directory.flavor() {
+        RuntimePathFlavor::Unix => '/',
+        RuntimePathFlavor::Windows => '\\',
+    };
+
+    let mut directory = directory.as_str().to_string();
+
+    if !directory.ends_with(separator) {
+        directory.push(separator);
+    }
+
+    directory
+}
+
+impl Kodi {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+
+        headers.insert("Content-Type", "application/json".parse()?);
+
+        self.request
+            .client_builder(headers)
+            .build()
+            .map_err(Into::into)
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> anyhow::Result<()> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join("jsonrpc")?;
+
+        let mut request = client.post(url).json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }));
+
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+
+        let response: RpcResponse = request.perform().await?.json().await?;
+
+        // JSON-RPC errors are returned with a 200 status
+        if let Some(error) = response.error {
+            return Err(anyhow::anyhow!(
+                "{method} failed: {} ({})",
+                error.message,
+                error.code
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn scan(&self, directory: &str) -> anyhow::Result<()> {
+        self.call(
+            &format!("{}.Scan", self.library.namespace()),
+            serde_json::json!({ "directory": directory, "showdialogs": false }),
+        )
+        .await
+    }
+
+    async fn clean(&self, directory: &str) -> anyhow::Result<()> {
+        // Only the video library can be cleaned by directory
+        let params = match self.library {
+            KodiLibrary::Video => {
+                serde_json::json!({ "directory": directory, "showdialogs": false })
+            }
+            KodiLibrary::Audio => serde_json::json!({ "showdialogs": false }),
+        };
+
+        self.call(&format!("{}.Clean", self.library.namespace()), params)
+            .await
+    }
+}
+
+impl TargetProcess for Kodi {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+        let mut directories: BTreeMap<String, Vec<&ScanEvent>> = BTreeMap::new();
+
+        for ev in evs {
+            directories
+                .entry(scan_directory(&ev.get_path(&self.rewrite)))
+                .or_default()
+                .push(ev);
+        }
+
+        for (directory, evs) in directories {
+            let outcome = match self.scan(&directory).await {
+                Ok(()) if self.clean && evs.iter().any(|ev| ev.kind() == EventKind::Deleted) => {
+                    self.clean(&directory).await
+                }
+                outcome => outcome,
+            };
+
+            match outcome {
+                Ok(()) => {
+                    debug!("scanned directory '{}'", directory);
+
+                    for ev in evs {
+                        result.succeed(&ev.id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to scan directory '{}': {}", directory, e);
+
+                    for ev in evs {
+                        result.fail(&ev.id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scan_directory;
+
+    #[test]
+    fn scan_directory_uses_the_parent_of_a_file_with_a_trailing_slash() {
+        assert_eq!(
+            scan_directory("/media/tv/Show/Season 1/episode.mkv"),
+            "/media/tv/Show/Season 1/"
+        );
+    }
+
+    #[test]
+    fn scan_directory_keeps_a_directory_and_its_trailing_slash() {
+        assert_eq!(
+            scan_directory("/media/movies/Movie"),
+            "/media/movies/Movie/"
+        );
+        assert_eq!(
+            scan_directory("/media/movies/Movie/"),
+            "/media/movies/Movie/"
+        );
+    }
+
+    #[test]
+    fn scan_directory_uses_backslashes_for_windows_paths() {
+        assert_eq!(
+            scan_directory(r"D:\Media\Movies\Movie\movie.mkv"),
+            r"D:\Media\Movies\Movie\"
+        );
+    }
+}
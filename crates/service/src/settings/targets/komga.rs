@@ -0,0 +1,172 @@
+use super::{Request, RequestBuilderPerform};
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use autopulse_utils::{get_url, RuntimePath};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{debug, error};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Komga {
+    /// URL to the Komga server
+    pub url: String,
+    /// API key for the Komga server
+    pub token: String,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize, Clone, Debug)]
+struct Library {
+    id: String,
+    name: String,
+    root: String,
+}
+
+/// Library whose root is the most specific match for `path`.
+fn get_library<'a>(libraries: &'a [Library], path: &str) -> Option<&'a Library> {
+    let event_path = RuntimePath::new(path);
+
+    libraries
+        .iter()
+        .filter(|library| event_path.starts_with(RuntimePath::new(&library.root)))
+        .max_by_key(|library| RuntimePath::new(&library.root).component_count())
+}
+
+impl Komga {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+
+        headers.insert("X-API-Key", self.token.parse()?);
+
+        self.request
+            .client_builder(headers)
+            .build()
+            .map_err(Into::into)
+    }
+
+    async fn libraries(&self) -> anyhow::Result<Vec<Library>> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join("api/v1/libraries")?;
+
+        let res = client.get(url).perform().await?;
+
+        Ok(res.json().await?)
+    }
+
+    async fn scan(&self, library: &Library) -> anyhow::Result<()> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join(&format!("api/v1/libraries/{}/scan", library.id))?;
+
+        client.post(url).perform().await.map(|_| ())
+    }
+}
+
+impl TargetProcess for Komga {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+
+        let libraries = self.libraries().await?;
+
+        if libraries.is_empty() {
+            return Err(anyhow::anyhow!("no libraries found"));
+        }
+
+        // Komga scans whole libraries, so each matched library is scanned once per batch
+        let mut to_scan: BTreeMap<&str, (&Library, Vec<&ScanEvent>)> = BTreeMap::new();
+
+        for ev in evs {
+            let ev_path = ev.get_path(&self.rewrite);
+
+            match get_library(&libraries, &ev_path) {
+                Some(library) => {
+                    to_scan
+                        .entry(&library.id)
+                        .or_insert_with(|| (library, vec![]))
+                        .1
+                        .push(ev);
+                }
+                None => {
+                    let e = anyhow::anyhow!("no library found for {}", ev_path);
+                    error!("{e}");
+                    result.fail(&ev.id, &e);
+                }
+            }
+        }
+
+        for (library, evs) in to_scan.into_values() {
+            match self.scan(library).await {
+                Ok(()) => {
+                    debug!("scanned library '{}'", library.name);
+
+                    for ev in evs {
+                        result.succeed(&ev.id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to scan library '{}': {}", library.name, e);
+
+                    for ev in evs {
+                        result.fail(&ev.id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_library, Library};
+
+    fn library(id: &str, root: &str) -> Library {
+        Library {
+            id: id.to_string(),
+            name: id.to_string(),
+            root: root.to_string(),
+        }
+    }
+
+    #[test]
+    fn get_library_prefers_the_most_specific_root() {
+        let libraries = vec![
+            library("comics", "/media/comics"),
+            library("manga", "/media/comics/manga"),
+        ];
+
+        assert_eq!(
+            get_library(&libraries, "/media/comics/manga/Series/v01.cbz").map(|l| l.id.as_str()),
+            Some("manga")
+        );
+        assert_eq!(
+            get_library(&libraries, "/media/comics/Series/001.cbz").map(|l| l.id.as_str()),
+            Some("comics")
+        );
+    }
+
+    #[test]
+    fn get_library_does_not_match_sibling_prefixes() {
+        let libraries = vec![library("comics", "/media/comics")];
+
+        assert!(get_library(&libraries, "/media/comics-old/Series/001.cbz").is_none());
+    }
+}
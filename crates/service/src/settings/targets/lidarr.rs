@@ -0,0 +1,166 @@
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use autopulse_utils::{get_url, RuntimePath};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::error;
+
+use super::{Request, RequestBuilderPerform};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Lidarr {
+    /// URL to the Lidarr server
+    pub url: String,
+    /// API token for the Lidarr server
+    pub token: String,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[derive(Deserialize, Debug)]
+struct LidarrArtist {
+    id: i64,
+    path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshArtist {
+    artist_id: i64,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "name")]
+#[serde(rename_all = "PascalCase")]
+enum Command {
+    RefreshArtist(RefreshArtist),
+}
+
+fn matching_artist_id(path: &str, artists: &[LidarrArtist]) -> Option<i64> {
+    let event = RuntimePath::new(path);
+    artists
+        .iter()
+        .filter(|artist| event.starts_with(RuntimePath::new(&artist.path)))
+        .max_by_key(|artist| RuntimePath::new(&artist.path).component_count())
+        .map(|artist| artist.id)
+}
+
+impl Lidarr {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+
+        headers.insert("X-Api-Key", self.token.parse()?);
+        headers.insert("Accept", "application/json".parse()?);
+
+        self.request
+            .client_builder(headers)
+            .build()
+            .map_err(Into::into)
+    }
+
+    async fn get_artists(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<(i64, Vec<String>)>> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join("api/v1/artist")?;
+        let mut to_be_refreshed: HashMap<i64, Vec<String>> = HashMap::new();
+
+        let res = client.get(url).perform().await?;
+
+        let artists = res.json::<Vec<LidarrArtist>>().await?;
+
+        for ev in evs {
+            let ev_path = ev.get_path(&self.rewrite);
+
+            if let Some(artist_id) = matching_artist_id(&ev_path, &artists) {
+                to_be_refreshed
+                    .entry(artist_id)
+                    .or_default()
+                    .push(ev.id.clone());
+            }
+        }
+
+        Ok(to_be_refreshed.into_iter().collect())
+    }
+
+    async fn refresh_artist(&self, artist_id: i64) -> anyhow::Result<()> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join("api/v1/command")?;
+        let payload = Command::RefreshArtist(RefreshArtist { artist_id });
+
+        client.post(url).json(&payload).perform().await.map(|_| ())
+    }
+}
+
+impl TargetProcess for Lidarr {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+
+        let artists = self.get_artists(evs).await?;
+
+        for (artist_id, ev_ids) in artists {
+            match self.refresh_artist(artist_id).await {
+                Ok(()) => {
+                    for id in &ev_ids {
+                        result.succeed(id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to refresh artist: {}", e);
+
+                    for id in &ev_ids {
+                        result.fail(id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_the_most_specific_artist_path() {
+        let artists = vec![
+            LidarrArtist {
+                id: 1,
+                path: "/music/Various Artists".to_string(),
+            },
+            LidarrArtist {
+                id: 2,
+                path: "/music/Various Artists/Soundtracks".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            matching_artist_id("/music/Various Artists/Soundtracks/Album/01.flac", &artists),
+            Some(2)
+        );
+        assert_eq!(
+            matching_artist_id("/music/Various Artists/Album/01.flac", &artists),
+            Some(1)
+        );
+        assert_eq!(
+            matching_artist_id("/music/Various/Album/01.flac", &artists),
+            None
+        );
+    }
+}
@@ -110,6 +110,113 @@ pub mod emby;
 ///
 /// See [`FileFlows`] for all options
 pub mod fileflows;
+/// Http - Generic HTTP target
+///
+/// This target is used to send a templated HTTP request per event or per batch, so services
+/// without a dedicated target can be integrated through config alone
+///
+/// See [`Template`](http::Template) for the available variables
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   my_service:
+///     type: http
+///     url: "http://localhost:8080/api/scan?path={{ rewritten_path | url }}"
+/// ```
+/// or
+/// ```yml
+/// targets:
+///   my_service:
+///     type: http
+///     method: PUT
+///     url: http://localhost:8080/api/scan
+///     headers:
+///       Content-Type: application/json
+///     body: '{"paths": {{ events }}}'
+///     batch: true
+///     success:
+///       status: [200, 202]
+///       pointer: /queued
+/// ```
+///
+/// See [`Http`] for all options
+pub mod http;
+/// Kavita - Kavita target
+///
+/// This target is used to scan the series folder of a file in a Kavita library
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   my_kavita:
+///     type: kavita
+///     url: http://localhost:5000
+///     token: "<API_KEY>"
+/// ```
+///
+/// See [`Kavita`] for all options
+pub mod kavita;
+/// Kodi - Kodi target
+///
+/// This target is used to scan a directory in the Kodi library over JSON-RPC
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   my_kodi:
+///     type: kodi
+///     url: http://localhost:8080
+/// ```
+/// or
+/// ```yml
+/// targets:
+///   my_kodi:
+///     type: kodi
+///     url: http://localhost:8080
+///     username: "kodi"
+///     password: "<PASSWORD>"
+///     library: audio
+///     clean: true
+/// ```
+///
+/// See [`Kodi`] for all options
+pub mod kodi;
+/// Komga - Komga target
+///
+/// This target is used to scan the Komga library containing a file
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   my_komga:
+///     type: komga
+///     url: http://localhost:25600
+///     token: "<API_KEY>"
+/// ```
+///
+/// See [`Komga`] for all options
+pub mod komga;
+/// Lidarr - Lidarr target
+///
+/// This target is used to refresh/rescan an artist in Lidarr
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   lidarr:
+///     type: lidarr
+///     url: http://localhost:8686
+///     token: "<API_KEY>"
+/// ```
+///
+/// See [`Lidarr`] for all options
+pub mod lidarr;
 /// Plex - Plex target
 ///
 /// This target is used to scan a file in Plex
@@ -152,6 +259,71 @@ pub mod plex;
 ///
 /// See [`Radarr`] for all options
 pub mod radarr;
+/// Rclone - rclone remote control target
+///
+/// This target is used to refresh the VFS directory cache of an rclone mount over the remote control API,
+/// so the media servers see new files without waiting for `--dir-cache-time`
+///
+/// The rewritten path must be relative to the root of the remote
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   my_rclone:
+///     type: rclone
+///     url: http://localhost:5572
+///     rewrite:
+///       from: "/mnt/media"
+///       to: ""
+/// ```
+/// or
+/// ```yml
+/// targets:
+///   my_rclone:
+///     type: rclone
+///     url: http://localhost:5572
+///     username: "rclone"
+///     password: "<PASSWORD>"
+///     fs: "gdrive:"
+///     recursive: true
+/// ```
+///
+/// Use `after` on the media server so it only scans once rclone can see the file
+///
+/// ```yml
+/// targets:
+///   my_rclone:
+///     type: rclone
+///     url: http://localhost:5572
+///     rewrite:
+///       from: "/mnt/media"
+///       to: ""
+///   my_plex:
+///     type: plex
+///     url: http://localhost:32400
+///     token: "<PLEX_TOKEN>"
+///     after: [my_rclone]
+/// ```
+///
+/// See [`Rclone`] for all options
+pub mod rclone;
+/// Readarr - Readarr target
+///
+/// This target is used to refresh/rescan an author in Readarr
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   readarr:
+///     type: readarr
+///     url: http://localhost:8787
+///     token: "<API_KEY>"
+/// ```
+///
+/// See [`Readarr`] for all options
+pub mod readarr;
 /// Sonarr - Sonarr target
 ///
 /// This target is used to refresh/rescan a series in Sonarr
@@ -168,6 +340,26 @@ pub mod radarr;
 ///
 /// See [`Sonarr`] for all options
 pub mod sonarr;
+/// Subsonic - Subsonic-compatible target
+///
+/// This target is used to scan the library of a Subsonic-compatible server such as Navidrome, Gonic or Airsonic
+///
+/// The server can only scan its whole library, so the scan is started once per batch and the events
+/// succeed when it finishes or `scan_timeout` passes, even when `opts.target_timeout` is shorter
+///
+/// # Example
+///
+/// ```yml
+/// targets:
+///   my_navidrome:
+///     type: subsonic
+///     url: http://localhost:4533
+///     username: "admin"
+///     password: "<PASSWORD>"
+/// ```
+///
+/// See [`Subsonic`] for all options
+pub mod subsonic;
 /// Tdarr - Tdarr target
 ///
 /// This target is used to process a file in Tdarr
@@ -188,12 +380,13 @@ pub mod tdarr;
 use crate::settings::{path_filter::PathFilter, rewrite::Rewrite};
 use audiobookshelf::Audiobookshelf;
 use autopulse_database::models::ScanEvent;
-use reqwest::{header, RequestBuilder, Response};
+use reqwest::{header, RequestBuilder, Response, Url};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use {
-    autopulse::Autopulse, command::Command, emby::Emby, fileflows::FileFlows, plex::Plex,
-    radarr::Radarr, sonarr::Sonarr, tdarr::Tdarr,
+    autopulse::Autopulse, command::Command, emby::Emby, fileflows::FileFlows, http::Http,
+    kavita::Kavita, kodi::Kodi, komga::Komga, lidarr::Lidarr, plex::Plex, radarr::Radarr,
+    rclone::Rclone, readarr::Readarr, sonarr::Sonarr, subsonic::Subsonic, tdarr::Tdarr,
 };
 
 /// HTTP request configuration options for targets
@@ -274,6 +467,14 @@ pub enum TargetType {
     FileFlows,
     Autopulse,
     Audiobookshelf,
+    Kodi,
+    Subsonic,
+    Komga,
+    Kavita,
+    Lidarr,
+    Readarr,
+    Rclone,
+    Http,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -289,10 +490,18 @@ pub enum Target {
     FileFlows(FileFlows),
     Autopulse(Autopulse),
     Audiobookshelf(Audiobookshelf),
+    Kodi(Kodi),
+    Subsonic(Subsonic),
+    Komga(Komga),
+    Kavita(Kavita),
+    Lidarr(Lidarr),
+    Readarr(Readarr),
+    Rclone(Rclone),
+    Http(Http),
 }
 
 impl Target {
-    fn rewrite(&self) -> &Option<Rewrite> {
+    pub fn rewrite(&self) -> &Option<Rewrite> {
         match self {
             Self::Plex(t) => &t.rewrite,
             Self::Jellyfin(t) | Self::Emby(t) => &t.rewrite,
@@ -303,10 +512,18 @@ impl Target {
             Self::FileFlows(t) => &t.rewrite,
             Self::Autopulse(t) => &t.rewrite,
             Self::Audiobookshelf(t) => &t.rewrite,
+            Self::Kodi(t) => &t.rewrite,
+            Self::Subsonic(t) => &t.rewrite,
+            Self::Komga(t) => &t.rewrite,
+            Self::Kavita(t) => &t.rewrite,
+            Self::Lidarr(t) => &t.rewrite,
+            Self::Readarr(t) => &t.rewrite,
+            Self::Rclone(t) => &t.rewrite,
+            Self::Http(t) => &t.rewrite,
         }
     }
 
-    fn filter(&self) -> &PathFilter {
+    pub fn filter(&self) -> &PathFilter {
         match self {
             Self::Plex(t) => &t.filter,
             Self::Jellyfin(t) | Self::Emby(t) => &t.filter,
@@ -317,6 +534,97 @@ impl Target {
             Self::FileFlows(t) => &t.filter,
             Self::Autopulse(t) => &t.filter,
             Self::Audiobookshelf(t) => &t.filter,
+            Self::Kodi(t) => &t.filter,
+            Self::Subsonic(t) => &t.filter,
+            Self::Komga(t) => &t.filter,
+            Self::Kavita(t) => &t.filter,
+            Self::Lidarr(t) => &t.filter,
+            Self::Readarr(t) => &t.filter,
+            Self::Rclone(t) => &t.filter,
+            Self::Http(t) => &t.filter,
+        }
+    }
+
+    pub fn concurrency(&self) -> Option<usize> {
+        match self {
+            Self::Plex(t) => t.concurrency,
+            Self::Jellyfin(t) | Self::Emby(t) => t.concurrency,
+            Self::Tdarr(t) => t.concurrency,
+            Self::Sonarr(t) => t.concurrency,
+            Self::Radarr(t) => t.concurrency,
+            Self::Command(t) => t.concurrency,
+            Self::FileFlows(t) => t.concurrency,
+            Self::Autopulse(t) => t.concurrency,
+            Self::Audiobookshelf(t) => t.concurrency,
+            Self::Kodi(t) => t.concurrency,
+            Self::Subsonic(t) => t.concurrency,
+            Self::Komga(t) => t.concurrency,
+            Self::Kavita(t) => t.concurrency,
+            Self::Lidarr(t) => t.concurrency,
+            Self::Readarr(t) => t.concurrency,
+            Self::Rclone(t) => t.concurrency,
+            Self::Http(t) => t.concurrency,
+        }
+    }
+
+    pub fn after(&self) -> &[String] {
+        match self {
+            Self::Plex(t) => &t.after,
+            Self::Jellyfin(t) | Self::Emby(t) => &t.after,
+            Self::Tdarr(t) => &t.after,
+            Self::Sonarr(t) => &t.after,
+            Self::Radarr(t) => &t.after,
+            Self::Command(t) => &t.after,
+            Self::FileFlows(t) => &t.after,
+            Self::Autopulse(t) => &t.after,
+            Self::Audiobookshelf(t) => &t.after,
+            Self::Kodi(t) => &t.after,
+            Self::Subsonic(t) => &t.after,
+            Self::Komga(t) => &t.after,
+            Self::Kavita(t) => &t.after,
+            Self::Lidarr(t) => &t.after,
+            Self::Readarr(t) => &t.after,
+            Self::Rclone(t) => &t.after,
+            Self::Http(t) => &t.after,
+        }
+    }
+
+    pub fn coalesce(&self) -> Option<u64> {
+        match self {
+            Self::Plex(t) => t.coalesce,
+            Self::Jellyfin(t) | Self::Emby(t) => t.coalesce,
+            Self::Tdarr(t) => t.coalesce,
+            Self::Sonarr(t) => t.coalesce,
+            Self::Radarr(t) => t.coalesce,
+            Self::Command(t) => t.coalesce,
+            Self::FileFlows(t) => t.coalesce,
+            Self::Autopulse(t) => t.coalesce,
+            Self::Audiobookshelf(t) => t.coalesce,
+            Self::Kodi(t) => t.coalesce,
+            Self::Subsonic(t) => t.coalesce,
+            Self::Komga(t) => t.coalesce,
+            Self::Kavita(t) => t.coalesce,
+            Self::Lidarr(t) => t.coalesce,
+            Self::Readarr(t) => t.coalesce,
+            Self::Rclone(t) => t.coalesce,
+            Self::Http(t) => t.coalesce,
+        }
+    }
+
+    /// Seconds the runner lets the target spend on one batch, `target_timeout` unless the
+    /// target waits on a scan of its own for longer.
+    pub fn timeout(&self, target_timeout: u64) -> u64 {
+        match self {
+            Self::Subsonic(t) => target_timeout.max(t.deadline()),
+            _ => target_timeout,
+        }
+    }
+
+    /// Checks the options that can't be validated while deserializing.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Http(t) => t.validate(),
+            _ => Ok(()),
         }
     }
 
@@ -324,17 +632,192 @@ impl Target {
         let path = ev.get_path(self.rewrite());
         self.filter().allows(&path)
     }
+
+    /// The path this target sees, rewritten with `rewrite` in place of its own when set.
+    pub fn target_path(&self, path: &str, rewrite: Option<&Rewrite>) -> String {
+        match rewrite.or(self.rewrite().as_ref()) {
+            Some(rewrite) => rewrite.rewrite_path(path.to_string()),
+            None => path.to_string(),
+        }
+    }
+
+    /// A copy of this target using `rewrite` in place of its own.
+    pub fn with_rewrite(&self, rewrite: &Rewrite) -> Self {
+        let mut target = self.clone();
+
+        let own = match &mut target {
+            Self::Plex(t) => &mut t.rewrite,
+            Self::Jellyfin(t) | Self::Emby(t) => &mut t.rewrite,
+            Self::Tdarr(t) => &mut t.rewrite,
+            Self::Sonarr(t) => &mut t.rewrite,
+            Self::Radarr(t) => &mut t.rewrite,
+            Self::Command(t) => &mut t.rewrite,
+            Self::FileFlows(t) => &mut t.rewrite,
+            Self::Autopulse(t) => &mut t.rewrite,
+            Self::Audiobookshelf(t) => &mut t.rewrite,
+            Self::Kodi(t) => &mut t.rewrite,
+            Self::Subsonic(t) => &mut t.rewrite,
+            Self::Komga(t) => &mut t.rewrite,
+            Self::Kavita(t) => &mut t.rewrite,
+            Self::Lidarr(t) => &mut t.rewrite,
+            Self::Readarr(t) => &mut t.rewrite,
+            Self::Rclone(t) => &mut t.rewrite,
+            Self::Http(t) => &mut t.rewrite,
+        };
+        *own = Some(rewrite.clone());
+
+        target
+    }
+}
+
+/// Groups the targets into stages that run one after another, each target
+/// placed after every target named in its `after`. Names are sorted within a stage.
+///
+/// Fails on unknown names and cycles.
+pub fn dispatch_stages(targets: &HashMap<String, Target>) -> anyhow::Result<Vec<Vec<&String>>> {
+    for (name, target) in targets {
+        for prerequisite in target.after() {
+            if !targets.contains_key(prerequisite) {
+                return Err(anyhow::anyhow!(
+                    "target '{name}' runs after unknown target '{prerequisite}'"
+                ));
+            }
+        }
+    }
+
+    let mut remaining = targets.iter().collect::<Vec<_>>();
+    remaining.sort_by_key(|(name, _)| *name);
+
+    let mut placed: HashSet<&String> = HashSet::new();
+    let mut stages = vec![];
+
+    while !remaining.is_empty() {
+        let (ready, waiting): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|(_, target)| target.after().iter().all(|p| placed.contains(p)));
+
+        if ready.is_empty() {
+            let names = waiting
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            return Err(anyhow::anyhow!(
+                "cycle in `after` involving targets: {names}"
+            ));
+        }
+
+        let stage = ready.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        placed.extend(stage.iter().copied());
+        stages.push(stage);
+        remaining = waiting;
+    }
+
+    Ok(stages)
+}
+
+/// Why a target could not process an event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessError {
+    /// HTTP status returned by the target, if the failure was an HTTP response.
+    pub status: Option<u16>,
+    /// The full error chain.
+    pub message: String,
+}
+
+impl From<&anyhow::Error> for ProcessError {
+    fn from(e: &anyhow::Error) -> Self {
+        Self {
+            status: e
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<HttpError>())
+                .map(|http| http.status.as_u16()),
+            // Request URLs can carry credentials, so they are redacted before the error is stored
+            message: e
+                .chain()
+                .map(|cause| match cause.downcast_ref::<reqwest::Error>() {
+                    Some(re) => match re.url() {
+                        Some(url) => re.to_string().replace(url.as_str(), &redact_url(url)),
+                        None => re.to_string(),
+                    },
+                    None => cause.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(": "),
+        }
+    }
+}
+
+/// Per-event outcome of [`TargetProcess::process`].
+///
+/// An event counts as processed only if it was marked succeeded and never
+/// marked failed; events the target does not mention are failures.
+#[derive(Default, Debug)]
+pub struct ProcessResult {
+    pub succeeded: HashSet<String>,
+    pub failed: HashMap<String, ProcessError>,
+}
+
+impl ProcessResult {
+    /// Adds the outcomes of `other`, a failure for an event wins over a success.
+    pub fn extend(&mut self, other: Self) {
+        self.succeeded.extend(other.succeeded);
+        self.failed.extend(other.failed);
+    }
+
+    pub fn succeed(&mut self, id: &str) {
+        self.succeeded.insert(id.to_string());
+    }
+
+    pub fn fail(&mut self, id: &str, e: &anyhow::Error) {
+        self.failed.insert(id.to_string(), e.into());
+    }
+
+    /// Gives each of `to` the outcome of `from`.
+    pub fn share(&mut self, from: &str, to: &[String]) {
+        match self.outcome(from) {
+            Ok(()) => self.succeeded.extend(to.iter().cloned()),
+            Err(e) => self
+                .failed
+                .extend(to.iter().map(|id| (id.clone(), e.clone()))),
+        }
+    }
+
+    pub fn outcome(&self, id: &str) -> Result<(), ProcessError> {
+        if let Some(e) = self.failed.get(id) {
+            return Err(e.clone());
+        }
+
+        if self.succeeded.contains(id) {
+            Ok(())
+        } else {
+            Err(ProcessError {
+                status: None,
+                message: "target did not report the file as processed".to_string(),
+            })
+        }
+    }
+}
+
+impl From<Vec<String>> for ProcessResult {
+    fn from(succeeded: Vec<String>) -> Self {
+        Self {
+            succeeded: succeeded.into_iter().collect(),
+            failed: HashMap::new(),
+        }
+    }
 }
 
 pub trait TargetProcess {
     fn process(
         &self,
         evs: &[&ScanEvent],
-    ) -> impl std::future::Future<Output = anyhow::Result<Vec<String>>> + Send;
+    ) -> impl std::future::Future<Output = anyhow::Result<ProcessResult>> + Send;
 }
 
 impl TargetProcess for Target {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
         match self {
             Self::Plex(t) => t.process(evs).await,
             Self::Jellyfin(t) | Self::Emby(t) => t.process(evs).await,
@@ -345,10 +828,43 @@ impl TargetProcess for Target {
             Self::FileFlows(t) => t.process(evs).await,
             Self::Autopulse(t) => t.process(evs).await,
             Self::Audiobookshelf(t) => t.process(evs).await,
+            Self::Kodi(t) => t.process(evs).await,
+            Self::Subsonic(t) => t.process(evs).await,
+            Self::Komga(t) => t.process(evs).await,
+            Self::Kavita(t) => t.process(evs).await,
+            Self::Lidarr(t) => t.process(evs).await,
+            Self::Readarr(t) => t.process(evs).await,
+            Self::Rclone(t) => t.process(evs).await,
+            Self::Http(t) => t.process(evs).await,
         }
     }
 }
 
+/// A request that reached the target but was answered with an error status.
+#[derive(Debug)]
+pub struct HttpError {
+    pub status: reqwest::StatusCode,
+    message: String,
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// `url` without its userinfo, query or fragment, any of which may hold credentials.
+pub fn redact_url(url: &Url) -> String {
+    let mut url = url.clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_query(None);
+    url.set_fragment(None);
+    url.to_string()
+}
+
 pub trait RequestBuilderPerform {
     fn perform(self) -> impl std::future::Future<Output = anyhow::Result<Response>> + Send;
 }
@@ -365,39 +881,49 @@ impl RequestBuilderPerform for RequestBuilder {
 
         match response {
             Ok(response) => {
-                if !response.status().is_success() {
-                    return Err(anyhow::anyhow!(
-                        // failed to PUT /path/to/file: 404 - Not Found
-                        "unable to {} {}: {} - {}",
-                        built.method(),
-                        built.url(),
-                        response.status(),
-                        response
-                            .text()
-                            .await
-                            .unwrap_or_else(|_| "unknown error".to_string()),
-                    ));
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(HttpError {
+                        status,
+                        message: format!(
+                            // failed to PUT /path/to/file: 404 - Not Found
+                            "unable to {} {}: {} - {}",
+                            built.method(),
+                            redact_url(built.url()),
+                            status,
+                            response
+                                .text()
+                                .await
+                                .unwrap_or_else(|_| "unknown error".to_string()),
+                        ),
+                    }
+                    .into());
                 }
 
                 Ok(response)
             }
 
             Err(e) => {
+                let e = e.without_url();
                 let status = e.status();
                 if let Some(status) = status {
-                    return Err(anyhow::anyhow!(
-                        "failed to {} {}: {} - {}",
-                        built.method(),
-                        built.url(),
+                    return Err(HttpError {
                         status,
-                        e
-                    ));
+                        message: format!(
+                            "failed to {} {}: {} - {}",
+                            built.method(),
+                            redact_url(built.url()),
+                            status,
+                            e
+                        ),
+                    }
+                    .into());
                 }
 
                 Err(anyhow::anyhow!(
                     "failed to {} {}: {}",
                     built.method(),
-                    built.url(),
+                    redact_url(built.url()),
                     e,
                 ))
             }
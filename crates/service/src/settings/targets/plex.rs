@@ -1,15 +1,20 @@
 use super::{Request, RequestBuilderPerform};
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use anyhow::Context;
-use autopulse_database::models::ScanEvent;
+use autopulse_database::models::{EventKind, ScanEvent};
 use autopulse_utils::{get_url, RuntimePath};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use tracing::{debug, error, trace};
 
+#[doc(hidden)]
+const fn default_true() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Plex {
     /// URL to the Plex server
@@ -22,11 +27,21 @@ pub struct Plex {
     /// Whether to analyze the file (default: false)
     #[serde(default)]
     pub analyze: bool,
+    /// Whether to empty the library trash after scanning a deleted file (default: true)
+    #[serde(default = "default_true")]
+    pub empty_trash: bool,
     /// Rewrite path for the file
     pub rewrite: Option<Rewrite>,
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -376,6 +391,14 @@ impl Plex {
         client.put(url).perform().await.map(|_| ())
     }
 
+    async fn empty_trash(&self, library: &Library) -> anyhow::Result<()> {
+        let client = self.get_client()?;
+        let url =
+            get_url(&self.url)?.join(&format!("library/sections/{}/emptyTrash", library.key))?;
+
+        client.put(url).perform().await.map(|_| ())
+    }
+
     async fn scan(&self, ev: &ScanEvent, library: &Library) -> anyhow::Result<()> {
         let client = self.get_client()?;
         let mut url =
@@ -390,25 +413,30 @@ impl Plex {
 }
 
 impl TargetProcess for Plex {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
         let libraries = self.libraries().await.context("failed to get libraries")?;
 
-        let mut succeeded: HashMap<String, bool> = HashMap::new();
+        let mut result = ProcessResult::default();
+        let mut emptied_libraries = HashSet::new();
 
         for ev in evs {
-            let succeeded_entry = succeeded.entry(ev.id.clone()).or_insert(true);
-
             let ev_path = ev.get_path(&self.rewrite);
             let matched_libraries = self.get_libraries(&libraries, &ev_path);
 
             if matched_libraries.is_empty() {
                 error!("no matching library for {ev_path}");
 
-                *succeeded_entry = false;
+                result.fail(
+                    &ev.id,
+                    &anyhow::anyhow!("no matching library for {ev_path}"),
+                );
 
                 continue;
             }
 
+            // Any failure recorded below takes precedence over this.
+            result.succeed(&ev.id);
+
             let mut processed_items = HashSet::new();
 
             for library in matched_libraries {
@@ -418,7 +446,20 @@ impl TargetProcess for Plex {
                     Ok(()) => {
                         debug!("scanned '{}'", ev_path);
 
-                        if self.analyze || self.refresh {
+                        if ev.kind() == EventKind::Deleted {
+                            if self.empty_trash && emptied_libraries.insert(library.key.clone()) {
+                                match self.empty_trash(&library).await {
+                                    Ok(()) => debug!("emptied trash for '{}'", library.title),
+                                    Err(e) => {
+                                        error!(
+                                            "failed to empty trash for '{}': {}",
+                                            library.title, e
+                                        );
+                                        result.fail(&ev.id, &e);
+                                    }
+                                }
+                            }
+                        } else if self.analyze || self.refresh {
                             match self.search_items(&library, &ev_path).await {
                                 Ok(items) => {
                                     if items.is_empty() {
@@ -431,11 +472,7 @@ impl TargetProcess for Plex {
                                     } else {
                                         trace!("found items for file '{}'", ev_path);
 
-                                        let mut all_success = true;
-
                                         for item in items {
-                                            let mut item_success = true;
-
                                             if processed_items.contains(&item.key) {
                                                 debug!(
                                                     "already processed item '{}' earlier, skipping",
@@ -454,7 +491,7 @@ impl TargetProcess for Plex {
                                                         "failed to refresh metadata for '{}': {}",
                                                         item.key, e
                                                     );
-                                                        item_success = false;
+                                                        result.fail(&ev.id, &e);
                                                     }
                                                 }
                                             }
@@ -469,42 +506,31 @@ impl TargetProcess for Plex {
                                                         "failed to analyze metadata for '{}': {}",
                                                         item.key, e
                                                     );
-                                                        item_success = false;
+                                                        result.fail(&ev.id, &e);
                                                     }
                                                 }
                                             }
 
-                                            if !item_success {
-                                                all_success = false;
-                                            }
-
                                             processed_items.insert(item.key);
                                         }
-
-                                        if !all_success {
-                                            *succeeded_entry = false;
-                                        }
                                     }
                                 }
                                 Err(e) => {
                                     error!("failed to get items for '{}': {:?}", ev_path, e);
-                                    *succeeded_entry = false;
+                                    result.fail(&ev.id, &e);
                                 }
                             };
                         }
                     }
                     Err(e) => {
                         error!("failed to scan file '{}': {}", ev_path, e);
-                        *succeeded_entry = false;
+                        result.fail(&ev.id, &e);
                     }
                 }
             }
         }
 
-        Ok(succeeded
-            .into_iter()
-            .filter_map(|(k, v)| if v { Some(k) } else { None })
-            .collect())
+        Ok(result)
     }
 }
 
@@ -518,8 +544,12 @@ mod tests {
             token: String::new(),
             refresh: false,
             analyze: false,
+            empty_trash: true,
             rewrite: None,
             filter: PathFilter::default(),
+            concurrency: None,
+            after: vec![],
+            coalesce: None,
             request: Request::default(),
         }
     }
@@ -560,8 +590,12 @@ mod tests {
             token: String::new(),
             refresh: false,
             analyze: false,
+            empty_trash: true,
             rewrite: None,
             filter: PathFilter::default(),
+            concurrency: None,
+            after: vec![],
+            coalesce: None,
             request: Request::default(),
         };
 
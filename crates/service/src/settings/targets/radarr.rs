@@ -1,6 +1,6 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use autopulse_database::models::ScanEvent;
 use autopulse_utils::{get_url, RuntimePath};
 use reqwest::header;
@@ -21,6 +21,13 @@ pub struct Radarr {
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -102,21 +109,27 @@ impl Radarr {
 }
 
 impl TargetProcess for Radarr {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
-        let mut succeeded = Vec::new();
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
 
         let movies = self.get_movies(evs).await?;
 
         match self.refresh_movies(movies).await {
             Ok(()) => {
-                succeeded.extend(evs.iter().map(|ev| ev.id.clone()));
+                for ev in evs {
+                    result.succeed(&ev.id);
+                }
             }
             Err(e) => {
                 error!("failed to refresh movies: {e}");
+
+                for ev in evs {
+                    result.fail(&ev.id, &e);
+                }
             }
         }
 
-        Ok(succeeded)
+        Ok(result)
     }
 }
 
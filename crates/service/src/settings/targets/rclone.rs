@@ -0,0 +1,206 @@
+use super::{Request, RequestBuilderPerform};
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use autopulse_utils::{get_url, RuntimePath};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, error, trace};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Rclone {
+    /// URL to the rclone remote control server
+    pub url: String,
+    /// Username for the remote control server (`--rc-user`)
+    pub username: Option<String>,
+    /// Password for the remote control server (`--rc-pass`)
+    pub password: Option<String>,
+    /// VFS to refresh when rclone serves more than one, e.g. `remote:` (default: the only VFS)
+    pub fs: Option<String>,
+    /// Whether to refresh the directory tree below each directory (default: false)
+    #[serde(default)]
+    pub recursive: bool,
+    /// Rewrite path for the file, the result must be relative to the root of the remote
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+struct RefreshResponse {
+    result: HashMap<String, String>,
+}
+
+/// Directory of `path` relative to the remote root, as rclone expects it.
+fn vfs_dir(path: &str) -> String {
+    RuntimePath::new(path)
+        .parent_or_self()
+        .normal_components()
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `dir` followed by each of its ancestors up to the remote root (`""`).
+fn with_ancestors(dir: &str) -> Vec<String> {
+    let mut dirs = vec![dir.to_string()];
+    let mut current = dir;
+
+    while !current.is_empty() {
+        current = current.rsplit_once('/').map_or("", |(parent, _)| parent);
+        dirs.push(current.to_string());
+    }
+
+    dirs
+}
+
+impl Rclone {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+
+        headers.insert("Content-Type", "application/json".parse()?);
+
+        self.request
+            .client_builder(headers)
+            .build()
+            .map_err(Into::into)
+    }
+
+    async fn call(
+        &self,
+        command: &str,
+        mut params: serde_json::Value,
+    ) -> anyhow::Result<reqwest::Response> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join(command)?;
+
+        if let Some(fs) = &self.fs {
+            params["fs"] = serde_json::Value::String(fs.clone());
+        }
+
+        let mut request = client.post(url).json(&params);
+
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+
+        request.perform().await
+    }
+
+    async fn forget(&self, dir: &str) -> anyhow::Result<()> {
+        self.call("vfs/forget", serde_json::json!({ "dir": dir }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Refreshes `dir`, walking up to the closest directory rclone already knows
+    /// about when `dir` itself was only just created.
+    async fn refresh(&self, dir: &str) -> anyhow::Result<()> {
+        let mut last_error = None;
+
+        for candidate in with_ancestors(dir) {
+            let res: RefreshResponse = self
+                .call(
+                    "vfs/refresh",
+                    serde_json::json!({ "dir": candidate, "recursive": self.recursive }),
+                )
+                .await?
+                .json()
+                .await?;
+
+            match res.result.get(&candidate).map(String::as_str) {
+                Some("OK") => {
+                    debug!("refreshed directory '{}'", candidate);
+                    return Ok(());
+                }
+                outcome => {
+                    let outcome = outcome.unwrap_or("no result").to_string();
+                    trace!("unable to refresh '{}': {}", candidate, outcome);
+                    last_error = Some(outcome);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "unable to refresh '{}': {}",
+            dir,
+            last_error.unwrap_or_default()
+        ))
+    }
+}
+
+impl TargetProcess for Rclone {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+        let mut dirs: BTreeMap<String, Vec<&ScanEvent>> = BTreeMap::new();
+
+        for ev in evs {
+            dirs.entry(vfs_dir(&ev.get_path(&self.rewrite)))
+                .or_default()
+                .push(ev);
+        }
+
+        for (dir, evs) in dirs {
+            let outcome = match self.forget(&dir).await {
+                Ok(()) => self.refresh(&dir).await,
+                Err(e) => Err(e),
+            };
+
+            match outcome {
+                Ok(()) => {
+                    for ev in evs {
+                        result.succeed(&ev.id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to refresh directory '{}': {}", dir, e);
+
+                    for ev in evs {
+                        result.fail(&ev.id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{vfs_dir, with_ancestors};
+
+    #[test]
+    fn vfs_dir_is_the_parent_relative_to_the_remote_root() {
+        assert_eq!(vfs_dir("/tv/Show/Season 1/episode.mkv"), "tv/Show/Season 1");
+        assert_eq!(vfs_dir("tv/Show/Season 1/"), "tv/Show/Season 1");
+        assert_eq!(vfs_dir("/episode.mkv"), "");
+    }
+
+    #[test]
+    fn vfs_dir_uses_forward_slashes_for_windows_paths() {
+        assert_eq!(vfs_dir(r"D:\tv\Show\episode.mkv"), "tv/Show");
+    }
+
+    #[test]
+    fn with_ancestors_ends_at_the_remote_root() {
+        assert_eq!(
+            with_ancestors("tv/Show/Season 1"),
+            vec!["tv/Show/Season 1", "tv/Show", "tv", ""]
+        );
+        assert_eq!(with_ancestors(""), vec![""]);
+    }
+}
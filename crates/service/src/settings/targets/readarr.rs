@@ -0,0 +1,156 @@
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use autopulse_utils::{get_url, RuntimePath};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::error;
+
+use super::{Request, RequestBuilderPerform};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Readarr {
+    /// URL to the Readarr server
+    pub url: String,
+    /// API token for the Readarr server
+    pub token: String,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReadarrAuthor {
+    id: i64,
+    path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshAuthor {
+    author_id: i64,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "name")]
+#[serde(rename_all = "PascalCase")]
+enum Command {
+    RefreshAuthor(RefreshAuthor),
+}
+
+fn matching_author_id(path: &str, authors: &[ReadarrAuthor]) -> Option<i64> {
+    let event = RuntimePath::new(path);
+    authors
+        .iter()
+        .filter(|author| event.starts_with(RuntimePath::new(&author.path)))
+        .max_by_key(|author| RuntimePath::new(&author.path).component_count())
+        .map(|author| author.id)
+}
+
+impl Readarr {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut headers = header::HeaderMap::new();
+
+        headers.insert("X-Api-Key", self.token.parse()?);
+        headers.insert("Accept", "application/json".parse()?);
+
+        self.request
+            .client_builder(headers)
+            .build()
+            .map_err(Into::into)
+    }
+
+    async fn get_authors(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<(i64, Vec<String>)>> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join("api/v1/author")?;
+        let mut to_be_refreshed: HashMap<i64, Vec<String>> = HashMap::new();
+
+        let res = client.get(url).perform().await?;
+
+        let authors = res.json::<Vec<ReadarrAuthor>>().await?;
+
+        for ev in evs {
+            let ev_path = ev.get_path(&self.rewrite);
+
+            if let Some(author_id) = matching_author_id(&ev_path, &authors) {
+                to_be_refreshed
+                    .entry(author_id)
+                    .or_default()
+                    .push(ev.id.clone());
+            }
+        }
+
+        Ok(to_be_refreshed.into_iter().collect())
+    }
+
+    async fn refresh_author(&self, author_id: i64) -> anyhow::Result<()> {
+        let client = self.get_client()?;
+        let url = get_url(&self.url)?.join("api/v1/command")?;
+        let payload = Command::RefreshAuthor(RefreshAuthor { author_id });
+
+        client.post(url).json(&payload).perform().await.map(|_| ())
+    }
+}
+
+impl TargetProcess for Readarr {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+
+        let authors = self.get_authors(evs).await?;
+
+        for (author_id, ev_ids) in authors {
+            match self.refresh_author(author_id).await {
+                Ok(()) => {
+                    for id in &ev_ids {
+                        result.succeed(id);
+                    }
+                }
+                Err(e) => {
+                    error!("failed to refresh author: {}", e);
+
+                    for id in &ev_ids {
+                        result.fail(id, &e);
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_windows_author_case_insensitively() {
+        let authors = vec![ReadarrAuthor {
+            id: 42,
+            path: r"D:\Books\Terry Pratchett".to_string(),
+        }];
+
+        assert_eq!(
+            matching_author_id(r"d:\books\terry pratchett\Mort\Mort.epub", &authors),
+            Some(42)
+        );
+        assert_eq!(
+            matching_author_id(r"D:\Books\Terry Pratchett Jr\Book.epub", &authors),
+            None
+        );
+    }
+}
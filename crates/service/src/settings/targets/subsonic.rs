@@ -0,0 +1,198 @@
+use super::{Request, RequestBuilderPerform};
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::targets::{ProcessResult, TargetProcess};
+use autopulse_database::models::ScanEvent;
+use autopulse_utils::{generate_uuid, get_url, md5_hex};
+use reqwest::header;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, warn};
+
+/// Subsonic API version sent with every request; 1.13.0 introduced token auth.
+const API_VERSION: &str = "1.13.0";
+/// Default seconds to wait for a scan to finish.
+const DEFAULT_SCAN_TIMEOUT: u64 = 300;
+/// Default seconds between scan status checks.
+const DEFAULT_POLL_INTERVAL: u64 = 5;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Subsonic {
+    /// URL to the Subsonic-compatible server
+    pub url: String,
+    /// Username for the server
+    pub username: String,
+    /// Password for the server, only ever sent as a salted token
+    pub password: String,
+    /// Seconds to wait for the scan to finish before counting it as done (default: 300)
+    pub scan_timeout: Option<u64>,
+    /// Seconds between scan status checks (default: 5)
+    pub poll_interval: Option<u64>,
+    /// Rewrite path for the file
+    pub rewrite: Option<Rewrite>,
+    /// Path filter matched against the target-rewritten path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
+    /// HTTP request options
+    #[serde(default)]
+    pub request: Request,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+struct SubsonicError {
+    code: i64,
+    message: Option<String>,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanStatus {
+    scanning: bool,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubsonicResponse {
+    status: String,
+    error: Option<SubsonicError>,
+    scan_status: Option<ScanStatus>,
+}
+
+#[doc(hidden)]
+#[derive(Deserialize)]
+struct SubsonicEnvelope {
+    #[serde(rename = "subsonic-response")]
+    response: SubsonicResponse,
+}
+
+/// `t` parameter of Subsonic token auth: `md5(password + salt)`.
+fn auth_token(password: &str, salt: &str) -> String {
+    md5_hex(format!("{password}{salt}").as_bytes())
+}
+
+impl Subsonic {
+    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+        self.request
+            .client_builder(header::HeaderMap::new())
+            .build()
+            .map_err(Into::into)
+    }
+
+    async fn call(&self, endpoint: &str) -> anyhow::Result<ScanStatus> {
+        let client = self.get_client()?;
+        let mut url = get_url(&self.url)?.join(&format!("rest/{endpoint}"))?;
+
+        let salt = generate_uuid().replace('-', "");
+
+        url.query_pairs_mut()
+            .append_pair("u", &self.username)
+            .append_pair("t", &auth_token(&self.password, &salt))
+            .append_pair("s", &salt)
+            .append_pair("v", API_VERSION)
+            .append_pair("c", "autopulse")
+            .append_pair("f", "json");
+
+        // The query holds the username and a replayable token, so errors leave the URL out
+        let envelope: SubsonicEnvelope = client
+            .get(url)
+            .perform()
+            .await?
+            .json()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        let response = envelope.response;
+
+        // Subsonic reports errors in the body of a 200 response
+        if response.status != "ok" {
+            let error = response.error.map_or_else(
+                || "unknown error".to_string(),
+                |e| format!("{} ({})", e.message.unwrap_or_default(), e.code),
+            );
+
+            return Err(anyhow::anyhow!("{endpoint} failed: {error}"));
+        }
+
+        response
+            .scan_status
+            .ok_or_else(|| anyhow::anyhow!("{endpoint} returned no scan status"))
+    }
+
+    /// Longest a scan can take: `scan_timeout`, then one more wait and status check.
+    pub fn deadline(&self) -> u64 {
+        self.scan_timeout.unwrap_or(DEFAULT_SCAN_TIMEOUT)
+            + self.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL)
+            + self.request.timeout.unwrap_or(Request::DEFAULT_TIMEOUT)
+    }
+
+    async fn scan(&self) -> anyhow::Result<()> {
+        let timeout = Duration::from_secs(self.scan_timeout.unwrap_or(DEFAULT_SCAN_TIMEOUT));
+        let interval = Duration::from_secs(self.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL));
+        let started = Instant::now();
+
+        let mut status = self.call("startScan").await?;
+
+        while status.scanning {
+            if started.elapsed() >= timeout {
+                warn!(
+                    "scan still running after {}s, counting it as done",
+                    timeout.as_secs()
+                );
+                return Ok(());
+            }
+
+            tokio::time::sleep(interval).await;
+
+            status = self.call("getScanStatus").await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TargetProcess for Subsonic {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
+        let mut result = ProcessResult::default();
+
+        // Subsonic can only scan the whole library, so one scan covers the batch
+        match self.scan().await {
+            Ok(()) => {
+                for ev in evs {
+                    debug!("scanned file: {}", ev.get_path(&self.rewrite));
+                    result.succeed(&ev.id);
+                }
+            }
+            Err(e) => {
+                error!("failed to scan library: {}", e);
+
+                for ev in evs {
+                    result.fail(&ev.id, &e);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::auth_token;
+
+    #[test]
+    fn auth_token_matches_the_subsonic_documentation() {
+        assert_eq!(
+            auth_token("sesame", "c19b2d"),
+            "26719a1196d2a940705a59634eb18eab"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
+use crate::settings::targets::{ProcessResult, TargetProcess};
 use autopulse_database::models::ScanEvent;
 use autopulse_utils::get_url;
 use serde::{Deserialize, Serialize};
@@ -18,6 +18,13 @@ pub struct Tdarr {
     /// Path filter matched against the target-rewritten path.
     #[serde(default)]
     pub filter: PathFilter,
+    /// Maximum number of events processed at once, one event per call (default: the whole batch in one call)
+    pub concurrency: Option<usize>,
+    /// Targets that must succeed for an event before it is sent to this one
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Seconds to wait for more events in the same directory, then send the directory once for all of them (default: no coalescing)
+    pub coalesce: Option<u64>,
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
@@ -81,13 +88,13 @@ impl Tdarr {
 }
 
 impl TargetProcess for Tdarr {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
-        let mut succeeded = Vec::new();
-
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<ProcessResult> {
         self.scan(evs).await?;
 
-        succeeded.extend(evs.iter().map(|ev| ev.id.clone()));
-
-        Ok(succeeded)
+        Ok(evs
+            .iter()
+            .map(|ev| ev.id.clone())
+            .collect::<Vec<_>>()
+            .into())
     }
 }
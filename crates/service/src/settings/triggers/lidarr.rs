@@ -3,8 +3,9 @@ use crate::settings::rewrite::Rewrite;
 use crate::settings::timer::EventTimers;
 use crate::settings::{
     timer::Timer,
-    triggers::{TriggerConfig, TriggerRequest},
+    triggers::{TriggerConfig, TriggerPath, TriggerRequest},
 };
+use autopulse_database::models::EventKind;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -96,11 +97,11 @@ impl TriggerRequest for LidarrRequest {
     fn from_json(json: serde_json::Value) -> anyhow::Result<Self> {
         serde_json::from_value(json).map_err(|e| anyhow::anyhow!(e))
     }
-    fn paths(&self) -> Vec<(String, bool)> {
+    fn events(&self) -> Vec<TriggerPath> {
         match self {
             Self::Download { track_files, .. } => track_files
                 .iter()
-                .map(|track_file| (track_file.path.clone(), true))
+                .map(|track_file| TriggerPath::new(track_file.path.clone(), EventKind::Created))
                 .collect(),
             Self::Rename {
                 renamed_track_files,
@@ -108,14 +109,20 @@ impl TriggerRequest for LidarrRequest {
                 let mut paths = vec![];
 
                 for file in renamed_track_files {
-                    paths.push((file.previous_path.clone(), false));
-                    paths.push((file.path.clone(), true));
+                    paths.push(TriggerPath::new(
+                        file.previous_path.clone(),
+                        EventKind::Deleted,
+                    ));
+                    paths.push(TriggerPath::renamed(
+                        file.path.clone(),
+                        file.previous_path.clone(),
+                    ));
                 }
 
                 paths
             }
             Self::ArtistDelete { artist } | Self::AlbumDelete { artist } => {
-                vec![(artist.path.clone(), false)]
+                vec![TriggerPath::new(artist.path.clone(), EventKind::Deleted)]
             }
             Self::Test | Self::Other => vec![],
         }
@@ -41,10 +41,11 @@ impl TriggerConfig for Manual {
 /// Example:
 /// - /triggers/manual?path=/path/to/file
 /// - /triggers/manual?path=/path/to/file&hash=3b3fa...
+/// - /triggers/manual?path=/path/to/file&hash=xxh3:9a4f3b2c1d0e8f76
 #[derive(Deserialize)]
 pub struct ManualQueryParams {
     /// Path to the file
     pub path: String,
-    /// Optional sha256sum hash of the file
+    /// Optional hash of the file, prefixed with `sha256:`, `sha1:`, `md5:`, `xxh3:` or `blake3:` (default: sha256)
     pub hash: Option<String>,
 }
@@ -231,11 +231,51 @@ pub mod sonarr;
 ///
 /// See [`Sportarr`] for all options
 pub mod sportarr;
+/// Sweep - Scheduled sweep trigger
+///
+/// Walks directories on a cron schedule and processes files modified since the last sweep.
+/// Useful for mounts where file events are unreliable, such as NFS or rclone
+///
+/// The first sweep only records its time, later sweeps pick up files modified since the previous one
+///
+/// # Example
+///
+/// ```yml
+/// triggers:
+///   my_sweep:
+///     type: sweep
+///     schedule: "0 */6 * * *"
+///     paths:
+///       - "/media"
+/// ```
+///
+/// or
+///
+/// ```yml
+/// triggers:
+///   my_sweep:
+///     type: sweep
+///     schedule: "30 3 * * *"
+///     paths:
+///       - "/mnt/remote/tv"
+///     depth: 3
+///     extensions: [ "mkv", "mp4" ]
+///     rewrite:
+///       from: "/mnt/remote"
+///       to: "/media"
+///     timer:
+///       wait: 30
+///     excludes: [ "ignored_target" ]
+/// ```
+///
+/// See [`Sweep`] for all options
+pub mod sweep;
 
 use crate::settings::path_filter::PathFilter;
 use crate::settings::timer::EventTimers;
 use crate::settings::timer::Timer;
 use crate::settings::{rewrite::Rewrite, triggers::autoscan::Autoscan};
+use autopulse_database::models::EventKind;
 use serde::{Deserialize, Serialize};
 use {
     lidarr::{Lidarr, LidarrRequest},
@@ -245,15 +285,58 @@ use {
     readarr::{Readarr, ReadarrRequest},
     sonarr::{Sonarr, SonarrRequest},
     sportarr::{Sportarr, SportarrRequest},
+    sweep::Sweep,
 };
 
+/// A path reported by a trigger request, along with what happened to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TriggerPath {
+    pub path: String,
+    pub kind: EventKind,
+    /// The path of the file before it was renamed.
+    pub previous_path: Option<String>,
+}
+
+impl TriggerPath {
+    pub fn new(path: String, kind: EventKind) -> Self {
+        Self {
+            path,
+            kind,
+            previous_path: None,
+        }
+    }
+
+    pub fn renamed(path: String, previous_path: String) -> Self {
+        Self {
+            path,
+            kind: EventKind::Renamed,
+            previous_path: Some(previous_path),
+        }
+    }
+
+    /// Whether the file should exist, and so be checked for before processing.
+    pub fn search(&self) -> bool {
+        self.kind != EventKind::Deleted
+    }
+}
+
 pub trait TriggerRequest {
     fn from_json(json: serde_json::Value) -> anyhow::Result<Self>
     where
         Self: Sized;
 
+    fn events(&self) -> Vec<TriggerPath>;
+
     // where the bool represents whether to check found status
-    fn paths(&self) -> Vec<(String, bool)>;
+    fn paths(&self) -> Vec<(String, bool)> {
+        self.events()
+            .into_iter()
+            .map(|event| {
+                let search = event.search();
+                (event.path, search)
+            })
+            .collect()
+    }
 }
 
 pub trait TriggerConfig {
@@ -278,6 +361,7 @@ pub enum TriggerType {
     Lidarr,
     Readarr,
     Notify,
+    Sweep,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -292,6 +376,7 @@ pub enum Trigger {
     Lidarr(Lidarr),
     Readarr(Readarr),
     Notify(Notify),
+    Sweep(Sweep),
 }
 
 impl Trigger {
@@ -305,6 +390,7 @@ impl Trigger {
             Self::Lidarr(trigger) => trigger,
             Self::Readarr(trigger) => trigger,
             Self::Notify(trigger) => trigger,
+            Self::Sweep(trigger) => trigger,
         }
     }
 
@@ -327,21 +413,32 @@ impl Trigger {
         base_timer
     }
 
-    pub fn paths(&self, body: serde_json::Value) -> anyhow::Result<(String, Vec<(String, bool)>)> {
+    pub fn paths(&self, body: serde_json::Value) -> anyhow::Result<(String, Vec<TriggerPath>)> {
         let event_name = body["eventType"].as_str().unwrap_or("unknown").to_string();
 
-        let paths = match &self {
-            Self::Sonarr(_) => Ok(SonarrRequest::from_json(body)?.paths()),
-            Self::Sportarr(_) => Ok(SportarrRequest::from_json(body)?.paths()),
-            Self::Radarr(_) => Ok(RadarrRequest::from_json(body)?.paths()),
-            Self::Lidarr(_) => Ok(LidarrRequest::from_json(body)?.paths()),
-            Self::Readarr(_) => Ok(ReadarrRequest::from_json(body)?.paths()),
-            Self::Manual(_) | Self::Notify(_) | Self::Autoscan(_) | Self::Bazarr(_) => {
-                Err(anyhow::anyhow!("Manual trigger does not have paths"))
-            }
+        let mut events = match &self {
+            Self::Sonarr(_) => Ok(SonarrRequest::from_json(body)?.events()),
+            Self::Sportarr(_) => Ok(SportarrRequest::from_json(body)?.events()),
+            Self::Radarr(_) => Ok(RadarrRequest::from_json(body)?.events()),
+            Self::Lidarr(_) => Ok(LidarrRequest::from_json(body)?.events()),
+            Self::Readarr(_) => Ok(ReadarrRequest::from_json(body)?.events()),
+            Self::Manual(_)
+            | Self::Notify(_)
+            | Self::Sweep(_)
+            | Self::Autoscan(_)
+            | Self::Bazarr(_) => Err(anyhow::anyhow!("Manual trigger does not have paths")),
         }?;
 
-        Ok((event_name, paths))
+        // An upgrade that keeps the same file name reports the path as both
+        // imported and deleted; the file still exists, so drop the delete.
+        let kept: Vec<String> = events
+            .iter()
+            .filter(|event| event.search())
+            .map(|event| event.path.clone())
+            .collect();
+        events.retain(|event| event.search() || !kept.contains(&event.path));
+
+        Ok((event_name, events))
     }
 
     pub fn excludes(&self) -> &Vec<String> {
@@ -2,19 +2,22 @@ use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
 use crate::settings::timer::Timer;
 use crate::settings::triggers::TriggerConfig;
+use crate::stability::FileState;
 use autopulse_utils::regex::Regex;
 use notify_debouncer_full::{
     new_debouncer, new_debouncer_opt,
     notify::{
-        event::{AccessKind, AccessMode, ModifyKind, RenameMode},
+        event::{
+            AccessKind, AccessMode, CreateKind, DataChange, ModifyKind, RemoveKind, RenameMode,
+        },
         Config, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode,
     },
     DebounceEventResult, Debouncer, NoCache, RecommendedCache,
 };
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, time::Duration};
+use std::{collections::HashMap, path::Path, path::PathBuf, time::Duration};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use tracing::{error, trace};
+use tracing::{error, trace, warn};
 
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Default)]
 pub enum NotifyBackendType {
@@ -44,6 +47,15 @@ impl NotifyBackend {
     }
 }
 
+/// A change seen by a notify trigger, with the path already filtered and rewritten.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotifyEvent {
+    pub path: String,
+    pub kind: EventKind,
+    /// The rewritten path the file had before it was renamed.
+    pub previous_path: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Notify {
     /// Paths to monitor
@@ -93,9 +105,10 @@ impl TriggerConfig for Notify {
 impl Notify {
     pub fn send_event(
         &self,
-        tx: UnboundedSender<(String, EventKind)>,
+        tx: UnboundedSender<NotifyEvent>,
         path: Option<&PathBuf>,
         reason: EventKind,
+        previous_path: Option<&PathBuf>,
     ) -> anyhow::Result<()> {
         if path.is_none() {
             return Ok(());
@@ -118,8 +131,11 @@ impl Notify {
             }
         }
 
+        let mut previous_path = previous_path.map(|path| path.to_string_lossy().to_string());
+
         if let Some(rewrite) = &self.rewrite {
             path = rewrite.rewrite_path(path);
+            previous_path = previous_path.map(|path| rewrite.rewrite_path(path));
         }
 
         if !self.filter.allows(&path) {
@@ -127,7 +143,12 @@ impl Notify {
             return Ok(());
         }
 
-        tx.send((path, reason)).map_err(|e| anyhow::anyhow!(e))
+        tx.send(NotifyEvent {
+            path,
+            kind: reason,
+            previous_path,
+        })
+        .map_err(|e| anyhow::anyhow!(e))
     }
 
     pub fn async_watcher(
@@ -160,7 +181,93 @@ impl Notify {
         }
     }
 
-    pub async fn watcher(&self, tx: UnboundedSender<(String, EventKind)>) -> anyhow::Result<()> {
+    /// Size and modified time of every file under `paths`, keyed by the path as watched.
+    pub fn snapshot(&self) -> HashMap<String, FileState> {
+        let mut files = HashMap::new();
+
+        for path in &self.paths {
+            self.snapshot_dir(Path::new(path), &mut files);
+        }
+
+        files
+    }
+
+    fn snapshot_dir(&self, dir: &Path, files: &mut HashMap<String, FileState>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("failed to read '{}' for snapshot: {e}", dir.display());
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                if self.recursive.unwrap_or(true) {
+                    self.snapshot_dir(&path, files);
+                }
+            } else if let Ok(state) = FileState::read(&path) {
+                files.insert(path.to_string_lossy().to_string(), state);
+            }
+        }
+    }
+
+    /// Sends the changes between two snapshots as if they had been watched, returning how many there were.
+    pub fn reconcile(
+        &self,
+        tx: UnboundedSender<NotifyEvent>,
+        previous: &HashMap<String, FileState>,
+        current: &HashMap<String, FileState>,
+    ) -> anyhow::Result<usize> {
+        let mut changes = 0;
+
+        for (path, state) in current {
+            let kind = match previous.get(path) {
+                None => EventKind::Create(CreateKind::File),
+                Some(before) if before != state => {
+                    EventKind::Modify(ModifyKind::Data(DataChange::Any))
+                }
+                Some(_) => continue,
+            };
+
+            self.send_event(tx.clone(), Some(&PathBuf::from(path)), kind, None)?;
+            changes += 1;
+        }
+
+        for path in previous.keys().filter(|path| !current.contains_key(*path)) {
+            self.send_event(
+                tx.clone(),
+                Some(&PathBuf::from(path)),
+                EventKind::Remove(RemoveKind::File),
+                None,
+            )?;
+            changes += 1;
+        }
+
+        Ok(changes)
+    }
+
+    pub async fn watcher(&self, tx: UnboundedSender<NotifyEvent>) -> anyhow::Result<()> {
+        self.observed_watcher(tx, None).await
+    }
+
+    /// Like [`watcher`](Self::watcher), also sending every watched path an event was seen for to `seen`.
+    pub async fn observed_watcher(
+        &self,
+        tx: UnboundedSender<NotifyEvent>,
+        seen: Option<UnboundedSender<PathBuf>>,
+    ) -> anyhow::Result<()> {
+        let observe = |path: &PathBuf| {
+            if let Some(seen) = &seen {
+                let _ = seen.send(path.clone());
+            }
+        };
+
         let (mut watcher, mut rx) = self.async_watcher()?;
 
         for path in &self.paths {
@@ -189,6 +296,27 @@ impl Notify {
                         let kind = debounced_event.event.kind;
 
                         match kind {
+                            // Split renames so the old path is seen leaving and the new one
+                            // arriving, the new one keeping where it came from
+                            EventKind::Modify(ModifyKind::Name(RenameMode::Both))
+                                if debounced_event.event.paths.len() == 2 =>
+                            {
+                                let paths = &debounced_event.event.paths;
+                                paths.iter().for_each(observe);
+
+                                self.send_event(
+                                    tx.clone(),
+                                    Some(&paths[0]),
+                                    EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                                    None,
+                                )?;
+                                self.send_event(
+                                    tx.clone(),
+                                    Some(&paths[1]),
+                                    EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                                    Some(&paths[0]),
+                                )?;
+                            }
                             EventKind::Access(AccessKind::Close(AccessMode::Write))
                             | EventKind::Modify(
                                 ModifyKind::Metadata(_) | ModifyKind::Name(RenameMode::Both),
@@ -196,7 +324,8 @@ impl Notify {
                             | EventKind::Create(_)
                             | EventKind::Remove(_) => {
                                 for path in debounced_event.event.paths {
-                                    self.send_event(tx.clone(), Some(&path), kind)?;
+                                    observe(&path);
+                                    self.send_event(tx.clone(), Some(&path), kind, None)?;
                                 }
                             }
                             _ => {}
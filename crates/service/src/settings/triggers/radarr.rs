@@ -1,7 +1,8 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
 use crate::settings::timer::{EventTimers, Timer};
-use crate::settings::triggers::{TriggerConfig, TriggerRequest};
+use crate::settings::triggers::{TriggerConfig, TriggerPath, TriggerRequest};
+use autopulse_database::models::EventKind;
 use autopulse_utils::join_path;
 use serde::{Deserialize, Serialize};
 
@@ -88,32 +89,41 @@ impl TriggerRequest for RadarrRequest {
     fn from_json(json: serde_json::Value) -> anyhow::Result<Self> {
         serde_json::from_value(json).map_err(|e| anyhow::anyhow!(e))
     }
-    fn paths(&self) -> Vec<(String, bool)> {
+    fn events(&self) -> Vec<TriggerPath> {
         match self {
             Self::MovieFileDelete { movie, movie_file } => {
-                vec![(
+                vec![TriggerPath::new(
                     join_path(&movie.folder_path, &movie_file.relative_path),
-                    false,
+                    EventKind::Deleted,
                 )]
             }
             Self::Rename { movie } => {
-                vec![(movie.folder_path.clone(), true)]
+                vec![TriggerPath::new(
+                    movie.folder_path.clone(),
+                    EventKind::Renamed,
+                )]
             }
             Self::MovieDelete { movie } => {
-                vec![(movie.folder_path.clone(), false)]
+                vec![TriggerPath::new(
+                    movie.folder_path.clone(),
+                    EventKind::Deleted,
+                )]
             }
             Self::Download {
                 movie,
                 movie_file,
                 deleted_files,
             } => {
-                let mut paths = vec![(
+                let mut paths = vec![TriggerPath::new(
                     join_path(&movie.folder_path, &movie_file.relative_path),
-                    true,
+                    EventKind::Created,
                 )];
 
                 for file in deleted_files {
-                    paths.push((join_path(&movie.folder_path, &file.relative_path), false));
+                    paths.push(TriggerPath::new(
+                        join_path(&movie.folder_path, &file.relative_path),
+                        EventKind::Deleted,
+                    ));
                 }
 
                 paths
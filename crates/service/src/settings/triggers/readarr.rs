@@ -1,7 +1,8 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
 use crate::settings::timer::{EventTimers, Timer};
-use crate::settings::triggers::{TriggerConfig, TriggerRequest};
+use crate::settings::triggers::{TriggerConfig, TriggerPath, TriggerRequest};
+use autopulse_database::models::EventKind;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -96,27 +97,33 @@ impl TriggerRequest for ReadarrRequest {
     fn from_json(json: serde_json::Value) -> anyhow::Result<Self> {
         serde_json::from_value(json).map_err(|e| anyhow::anyhow!(e))
     }
-    fn paths(&self) -> Vec<(String, bool)> {
+    fn events(&self) -> Vec<TriggerPath> {
         match self {
             Self::Download { book_files } => book_files
                 .iter()
-                .map(|book_file| (book_file.path.clone(), true))
+                .map(|book_file| TriggerPath::new(book_file.path.clone(), EventKind::Created))
                 .collect(),
             Self::Rename { renamed_book_files } => {
                 let mut paths = vec![];
 
                 for file in renamed_book_files {
-                    paths.push((file.previous_path.clone(), false));
-                    paths.push((file.path.clone(), true));
+                    paths.push(TriggerPath::new(
+                        file.previous_path.clone(),
+                        EventKind::Deleted,
+                    ));
+                    paths.push(TriggerPath::renamed(
+                        file.path.clone(),
+                        file.previous_path.clone(),
+                    ));
                 }
 
                 paths
             }
             Self::AuthorDelete { author } | Self::BookDelete { author } => {
-                vec![(author.path.clone(), false)]
+                vec![TriggerPath::new(author.path.clone(), EventKind::Deleted)]
             }
             Self::BookFileDelete { book_file } => {
-                vec![(book_file.path.clone(), false)]
+                vec![TriggerPath::new(book_file.path.clone(), EventKind::Deleted)]
             }
             Self::Test | Self::Other => vec![],
         }
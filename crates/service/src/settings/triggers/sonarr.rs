@@ -1,7 +1,8 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
 use crate::settings::timer::{EventTimers, Timer};
-use crate::settings::triggers::{TriggerConfig, TriggerRequest};
+use crate::settings::triggers::{TriggerConfig, TriggerPath, TriggerRequest};
+use autopulse_database::models::EventKind;
 use autopulse_utils::join_path;
 use serde::{Deserialize, Serialize};
 
@@ -106,13 +107,16 @@ impl TriggerRequest for SonarrRequest {
         serde_json::from_value(json).map_err(|e| anyhow::anyhow!(e))
     }
 
-    fn paths(&self) -> Vec<(String, bool)> {
+    fn events(&self) -> Vec<TriggerPath> {
         match self {
             Self::EpisodeFileDelete {
                 episode_file,
                 series,
             } => {
-                vec![(join_path(&series.path, &episode_file.relative_path), false)]
+                vec![TriggerPath::new(
+                    join_path(&series.path, &episode_file.relative_path),
+                    EventKind::Deleted,
+                )]
             }
             Self::Rename {
                 series,
@@ -121,31 +125,48 @@ impl TriggerRequest for SonarrRequest {
                 let mut paths = vec![];
 
                 for file in renamed_episode_files {
-                    paths.push((file.previous_path.clone(), false));
-                    paths.push((join_path(&series.path, &file.relative_path), true));
+                    paths.push(TriggerPath::new(
+                        file.previous_path.clone(),
+                        EventKind::Deleted,
+                    ));
+                    paths.push(TriggerPath::renamed(
+                        join_path(&series.path, &file.relative_path),
+                        file.previous_path.clone(),
+                    ));
                 }
 
                 paths
             }
-            Self::SeriesDelete { series } => vec![(series.path.clone(), false)],
+            Self::SeriesDelete { series } => {
+                vec![TriggerPath::new(series.path.clone(), EventKind::Deleted)]
+            }
             Self::Download {
                 episode_file,
                 episode_files,
                 series,
                 deleted_files,
             } => {
-                let mut paths: Vec<(String, bool)> = vec![];
+                let mut paths: Vec<TriggerPath> = vec![];
 
                 if let Some(ef) = episode_file {
-                    paths.push((join_path(&series.path, &ef.relative_path), true));
+                    paths.push(TriggerPath::new(
+                        join_path(&series.path, &ef.relative_path),
+                        EventKind::Created,
+                    ));
                 }
 
                 for ef in episode_files {
-                    paths.push((join_path(&series.path, &ef.relative_path), true));
+                    paths.push(TriggerPath::new(
+                        join_path(&series.path, &ef.relative_path),
+                        EventKind::Created,
+                    ));
                 }
 
                 for file in deleted_files {
-                    paths.push((join_path(&series.path, &file.relative_path), false));
+                    paths.push(TriggerPath::new(
+                        join_path(&series.path, &file.relative_path),
+                        EventKind::Deleted,
+                    ));
                 }
 
                 paths
@@ -1,7 +1,8 @@
 use crate::settings::path_filter::PathFilter;
 use crate::settings::rewrite::Rewrite;
 use crate::settings::timer::{EventTimers, Timer};
-use crate::settings::triggers::{TriggerConfig, TriggerRequest};
+use crate::settings::triggers::{TriggerConfig, TriggerPath, TriggerRequest};
+use autopulse_database::models::EventKind;
 use autopulse_utils::join_path;
 use serde::{Deserialize, Serialize};
 
@@ -99,7 +100,7 @@ impl TriggerRequest for SportarrRequest {
         serde_json::from_value(json).map_err(|e| anyhow::anyhow!(e))
     }
 
-    fn paths(&self) -> Vec<(String, bool)> {
+    fn events(&self) -> Vec<TriggerPath> {
         match self {
             Self::EpisodeFileDelete {
                 deleted_files,
@@ -111,18 +112,23 @@ impl TriggerRequest for SportarrRequest {
 
                 deleted_files
                     .iter()
-                    .map(|f| (join_path(series_path, &f.relative_path), false))
+                    .map(|f| {
+                        TriggerPath::new(
+                            join_path(series_path, &f.relative_path),
+                            EventKind::Deleted,
+                        )
+                    })
                     .collect()
             }
             Self::Rename { series } => series
                 .path
                 .clone()
-                .map(|path| vec![(path, true)])
+                .map(|path| vec![TriggerPath::new(path, EventKind::Renamed)])
                 .unwrap_or_default(),
             Self::SeriesDelete { series } => series
                 .path
                 .clone()
-                .map(|path| vec![(path, false)])
+                .map(|path| vec![TriggerPath::new(path, EventKind::Deleted)])
                 .unwrap_or_default(),
             Self::Download {
                 episode_file,
@@ -130,22 +136,31 @@ impl TriggerRequest for SportarrRequest {
                 series,
                 deleted_files,
             } => {
-                let mut paths: Vec<(String, bool)> = vec![];
+                let mut paths: Vec<TriggerPath> = vec![];
 
                 let Some(series_path) = series.path.as_ref() else {
                     return paths;
                 };
 
                 if let Some(ef) = episode_file {
-                    paths.push((join_path(series_path, &ef.relative_path), true));
+                    paths.push(TriggerPath::new(
+                        join_path(series_path, &ef.relative_path),
+                        EventKind::Created,
+                    ));
                 }
 
                 for ef in episode_files {
-                    paths.push((join_path(series_path, &ef.relative_path), true));
+                    paths.push(TriggerPath::new(
+                        join_path(series_path, &ef.relative_path),
+                        EventKind::Created,
+                    ));
                 }
 
                 for file in deleted_files {
-                    paths.push((join_path(series_path, &file.relative_path), false));
+                    paths.push(TriggerPath::new(
+                        join_path(series_path, &file.relative_path),
+                        EventKind::Deleted,
+                    ));
                 }
 
                 paths
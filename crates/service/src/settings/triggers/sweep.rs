@@ -0,0 +1,173 @@
+use crate::settings::path_filter::PathFilter;
+use crate::settings::rewrite::Rewrite;
+use crate::settings::timer::Timer;
+use crate::settings::triggers::TriggerConfig;
+use autopulse_utils::regex::Regex;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use tracing::{trace, warn};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Sweep {
+    /// Cron expression for when to sweep, either `min hour day month weekday` or with a leading seconds field (e.g. `0 */6 * * *`)
+    pub schedule: String,
+    /// Root paths to walk
+    pub paths: Vec<String>,
+    /// Rewrite path
+    pub rewrite: Option<Rewrite>,
+    /// Filter by regex
+    pub filters: Option<Vec<String>>,
+    /// Deepest directory level to walk below each root, 0 to only sweep the root itself (default: unlimited)
+    pub depth: Option<usize>,
+    /// Only sweep files with these extensions, without the dot (default: any extension)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// Targets to exclude
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    /// Path filter matched against the rewritten file path.
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Timer
+    pub timer: Option<Timer>,
+}
+
+impl TriggerConfig for Sweep {
+    fn rewrite(&self) -> Option<&Rewrite> {
+        self.rewrite.as_ref()
+    }
+
+    fn timer(&self) -> Option<&Timer> {
+        self.timer.as_ref()
+    }
+
+    fn excludes(&self) -> &Vec<String> {
+        &self.excludes
+    }
+
+    fn filter(&self) -> &PathFilter {
+        &self.filter
+    }
+}
+
+impl Sweep {
+    pub fn cron(&self) -> anyhow::Result<cron::Schedule> {
+        let schedule = self.schedule.trim();
+
+        // The cron crate expects a seconds field, accept the usual five field form too
+        let expression = if schedule.split_whitespace().count() == 5 {
+            format!("0 {schedule}")
+        } else {
+            schedule.to_string()
+        };
+
+        cron::Schedule::from_str(&expression)
+            .map_err(|e| anyhow::anyhow!("invalid sweep schedule '{schedule}': {e}"))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.cron()?;
+
+        for regex in self.filters.iter().flatten() {
+            Regex::new(regex)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rewritten paths of the files under `paths` modified after `since`.
+    pub fn scan(&self, since: NaiveDateTime) -> anyhow::Result<Vec<String>> {
+        let filters = self
+            .filters
+            .iter()
+            .flatten()
+            .map(|regex| Regex::new(regex))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut found = vec![];
+
+        for root in &self.paths {
+            self.walk(Path::new(root), 0, since, &filters, &mut found);
+        }
+
+        Ok(found)
+    }
+
+    fn walk(
+        &self,
+        dir: &Path,
+        depth: usize,
+        since: NaiveDateTime,
+        filters: &[Regex],
+        found: &mut Vec<String>,
+    ) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("failed to read '{}' during sweep: {e}", dir.display());
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                if self.depth.is_none_or(|max| depth < max) {
+                    self.walk(&path, depth + 1, since, filters, found);
+                }
+                continue;
+            }
+
+            if !self.extensions.is_empty() {
+                let matches = path.extension().is_some_and(|ext| {
+                    self.extensions
+                        .iter()
+                        .any(|allowed| ext.eq_ignore_ascii_case(allowed.trim_start_matches('.')))
+                });
+
+                if !matches {
+                    continue;
+                }
+            }
+
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map(|at| chrono::DateTime::<chrono::Utc>::from(at).naive_utc());
+
+            if !modified.is_ok_and(|modified| modified > since) {
+                continue;
+            }
+
+            if let Some(path) = self.accept(&path.to_string_lossy(), filters) {
+                found.push(path);
+            }
+        }
+    }
+
+    fn accept(&self, path: &str, filters: &[Regex]) -> Option<String> {
+        if !filters.is_empty() && !filters.iter().any(|regex| regex.is_match(path)) {
+            return None;
+        }
+
+        let mut path = path.to_string();
+
+        if let Some(rewrite) = &self.rewrite {
+            path = rewrite.rewrite_path(path);
+        }
+
+        if !self.filter.allows(&path) {
+            trace!("sweep trigger filtered path '{path}'");
+            return None;
+        }
+
+        Some(path)
+    }
+}
@@ -174,29 +174,31 @@ impl DiscordWebhook {
             let timestamp = chrono::Utc::now().to_rfc3339();
 
             let color = match event {
-                EventType::New => 6_061_450,     // grey
-                EventType::Found => 52084,       // green
-                EventType::Failed => 16_711_680, // red
-                EventType::Processed => 39129,   // blue
+                EventType::New => 6_061_450,                             // grey
+                EventType::Found | EventType::TargetUp => 52084,         // green
+                EventType::Failed | EventType::TargetDown => 16_711_680, // red
+                EventType::Processed => 39129,                           // blue
                 EventType::Retrying | EventType::HashMismatch => 16_776_960,
             };
 
             let title = trigger.clone().map_or_else(
                 || {
                     format!(
-                        "[{}] - {} file{} {}",
+                        "[{}] - {} {}{} {}",
                         event,
                         files.len(),
+                        event.subject(),
                         sify(files),
                         event.action()
                     )
                 },
                 |trigger| {
                     format!(
-                        "[{}] - [{}] - {} file{} {}",
+                        "[{}] - [{}] - {} {}{} {}",
                         event,
                         trigger,
                         files.len(),
+                        event.subject(),
                         sify(files),
                         event.action()
                     )
@@ -209,7 +211,11 @@ impl DiscordWebhook {
                     value: timestamp.clone(),
                 },
                 DiscordEmbedField {
-                    name: "Files".to_string(),
+                    name: match event.subject() {
+                        "target" => "Targets",
+                        _ => "Files",
+                    }
+                    .to_string(),
                     // value: files.join("\n"),
                     value: Self::truncate_message(files.join("\n"), 1024),
                 },
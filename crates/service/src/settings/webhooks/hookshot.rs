@@ -23,16 +23,18 @@ impl HookshotWebhook {
         trigger.map_or_else(
             || {
                 format!(
-                    "[{event}] - {} file{} {}",
+                    "[{event}] - {} {}{} {}",
                     files.len(),
+                    event.subject(),
                     sify(files),
                     event.action()
                 )
             },
             |trigger| {
                 format!(
-                    "[{event}] - [{trigger}] - {} file{} {}",
+                    "[{event}] - [{trigger}] - {} {}{} {}",
                     files.len(),
+                    event.subject(),
                     sify(files),
                     event.action()
                 )
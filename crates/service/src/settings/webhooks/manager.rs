@@ -1,3 +1,4 @@
+use crate::metrics::Metrics;
 use crate::settings::Settings;
 use futures::future::join_all;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,10 @@ pub enum EventType {
     Processed = 4,
     /// Failed event
     Failed = 5,
+    /// Target paused by its circuit breaker
+    TargetDown = 6,
+    /// Target resumed after a successful probe
+    TargetUp = 7,
 }
 
 impl Display for EventType {
@@ -35,6 +40,8 @@ impl Display for EventType {
             Self::Failed => "FAILED",
             Self::Processed => "PROCESSED",
             Self::HashMismatch => "HASH MISMATCH",
+            Self::TargetDown => "TARGET DOWN",
+            Self::TargetUp => "TARGET UP",
         };
 
         write!(f, "{event}")
@@ -50,6 +57,8 @@ impl EventType {
             Self::Failed => "failed",
             Self::Processed => "processed",
             Self::HashMismatch => "hash_mismatch",
+            Self::TargetDown => "target_down",
+            Self::TargetUp => "target_up",
         }
     }
 
@@ -61,6 +70,16 @@ impl EventType {
             Self::Failed => "failed",
             Self::Processed => "processed",
             Self::HashMismatch => "mismatched",
+            Self::TargetDown => "paused",
+            Self::TargetUp => "resumed",
+        }
+    }
+
+    /// What the names sent with the event are, target events list target names instead of files.
+    pub const fn subject(&self) -> &'static str {
+        match self {
+            Self::TargetDown | Self::TargetUp => "target",
+            _ => "file",
         }
     }
 }
@@ -68,13 +87,15 @@ impl EventType {
 #[derive(Clone)]
 pub struct WebhookManager {
     settings: Arc<Settings>,
+    metrics: Arc<Metrics>,
     queue: Arc<RwLock<WebhookQueue>>,
 }
 
 impl WebhookManager {
-    pub fn new(settings: Arc<Settings>) -> Self {
+    pub fn new(settings: Arc<Settings>, metrics: Arc<Metrics>) -> Self {
         Self {
             settings,
+            metrics,
             queue: Arc::new(RwLock::new(HashMap::new())),
         }
     }
@@ -106,9 +127,18 @@ impl WebhookManager {
             .map(|(name, webhook)| {
                 let batch = &batch;
                 async move {
-                    if let Err(e) = webhook.send(batch, retries, timeout_secs).await {
-                        error!("failed to send webhook '{}': {}", name, e);
-                    }
+                    let outcome = match webhook.send(batch, retries, timeout_secs).await {
+                        Ok(()) => "success",
+                        Err(e) => {
+                            error!("failed to send webhook '{}': {}", name, e);
+                            "failure"
+                        }
+                    };
+
+                    self.metrics
+                        .webhook_sends
+                        .with_label_values(&[name.as_str(), outcome])
+                        .inc();
                 }
             })
             .collect();
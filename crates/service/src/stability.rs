@@ -0,0 +1,202 @@
+use crate::settings::opts::Stability;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Size and modified time of a file at one observation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileState {
+    pub size: u64,
+    pub modified: Option<NaiveDateTime>,
+}
+
+impl FileState {
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+
+        Ok(Self {
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .map(|at| chrono::DateTime::<chrono::Utc>::from(at).naive_utc()),
+        })
+    }
+}
+
+struct Observation {
+    state: FileState,
+    at: NaiveDateTime,
+    /// Observations in a row that saw `state`.
+    count: u32,
+}
+
+/// Tracks the files of unstable events until their size and modified time stop changing.
+///
+/// Observations are kept in memory, so a restart begins counting again.
+pub struct StabilityTracker {
+    stability: Option<Stability>,
+    observations: Mutex<HashMap<String, Observation>>,
+}
+
+impl StabilityTracker {
+    /// Without `stability` every file is stable as soon as it exists.
+    pub fn new(stability: Option<Stability>) -> Self {
+        Self {
+            stability,
+            observations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub const fn enabled(&self) -> bool {
+        self.stability.is_some()
+    }
+
+    /// Observes the file of an event, returning whether it has stopped changing.
+    pub fn observe(&self, id: &str, path: &Path, now: NaiveDateTime) -> anyhow::Result<bool> {
+        if !self.enabled() {
+            return Ok(true);
+        }
+
+        Ok(self.record(id, FileState::read(path)?, now))
+    }
+
+    pub fn record(&self, id: &str, state: FileState, now: NaiveDateTime) -> bool {
+        let Some(stability) = &self.stability else {
+            return true;
+        };
+
+        let mut observations = self.observations.lock().expect("stability lock poisoned");
+
+        let quiet = stability
+            .quiet
+            .zip(state.modified)
+            .is_some_and(|(quiet, modified)| {
+                now - modified >= chrono::Duration::seconds(quiet as i64)
+            });
+
+        if quiet {
+            observations.remove(id);
+            return true;
+        }
+
+        let interval = chrono::Duration::seconds(stability.interval as i64);
+        let observation = observations
+            .entry(id.to_string())
+            .or_insert_with(|| Observation {
+                state,
+                at: now,
+                count: 0,
+            });
+
+        if observation.count > 0 && now - observation.at < interval {
+            return false;
+        }
+
+        observation.count = if observation.state == state {
+            observation.count + 1
+        } else {
+            1
+        };
+        observation.state = state;
+        observation.at = now;
+
+        if observation.count >= stability.checks {
+            observations.remove(id);
+            return true;
+        }
+
+        false
+    }
+
+    /// Drops the observations of an event, e.g. when its file went missing again.
+    pub fn forget(&self, id: &str) {
+        self.observations
+            .lock()
+            .expect("stability lock poisoned")
+            .remove(id);
+    }
+
+    /// Keeps only the observations of events `keep` accepts, the rest no longer wait for a file.
+    pub fn retain(&self, mut keep: impl FnMut(&str) -> bool) {
+        self.observations
+            .lock()
+            .expect("stability lock poisoned")
+            .retain(|id, _| keep(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    fn state(size: u64, modified: i64) -> FileState {
+        FileState {
+            size,
+            modified: Some(at(modified)),
+        }
+    }
+
+    fn tracker(checks: u32, interval: u64, quiet: Option<u64>) -> StabilityTracker {
+        StabilityTracker::new(Some(Stability {
+            checks,
+            interval,
+            quiet,
+        }))
+    }
+
+    #[test]
+    fn stable_after_identical_observations_in_a_row() {
+        let tracker = tracker(3, 5, None);
+
+        assert!(!tracker.record("a", state(10, 0), at(0)));
+        assert!(!tracker.record("a", state(20, 4), at(5)));
+        assert!(!tracker.record("a", state(20, 4), at(10)));
+        assert!(tracker.record("a", state(20, 4), at(15)));
+    }
+
+    #[test]
+    fn observations_closer_than_the_interval_do_not_count() {
+        let tracker = tracker(2, 5, None);
+
+        assert!(!tracker.record("a", state(10, 0), at(0)));
+        assert!(!tracker.record("a", state(10, 0), at(1)));
+        assert!(!tracker.record("a", state(10, 0), at(4)));
+        assert!(tracker.record("a", state(10, 0), at(5)));
+    }
+
+    #[test]
+    fn quiet_files_are_stable_straight_away() {
+        let tracker = tracker(3, 5, Some(60));
+
+        assert!(!tracker.record("a", state(10, 0), at(30)));
+        assert!(tracker.record("b", state(10, 0), at(60)));
+    }
+
+    #[test]
+    fn retain_drops_events_that_stopped_waiting() {
+        let tracker = tracker(3, 5, None);
+
+        assert!(!tracker.record("a", state(10, 0), at(0)));
+        assert!(!tracker.record("b", state(10, 0), at(0)));
+        tracker.retain(|id| id == "b");
+
+        let observations = tracker.observations.lock().unwrap();
+        assert!(!observations.contains_key("a"));
+        assert!(observations.contains_key("b"));
+    }
+
+    #[test]
+    fn disabled_tracker_treats_every_file_as_stable() {
+        let tracker = StabilityTracker::new(None);
+
+        assert!(tracker.record("a", state(10, 0), at(0)));
+    }
+}
@@ -1,14 +1,14 @@
 #[cfg(feature = "sqlite")]
 use crate::tests::util::fresh_manager;
-#[cfg(feature = "postgres")]
+#[cfg(any(feature = "postgres", feature = "mysql"))]
 use crate::{manager::PulseManager, settings::Settings};
-#[cfg(any(feature = "postgres", feature = "sqlite"))]
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use autopulse_database::conn::get_conn;
-#[cfg(feature = "postgres")]
+#[cfg(any(feature = "postgres", feature = "mysql"))]
 use autopulse_database::conn::get_pool;
 use autopulse_database::models::NewScanEvent;
 #[cfg(feature = "sqlite")]
-use autopulse_database::models::{FoundStatus, ProcessStatus};
+use autopulse_database::models::{EventKind, FoundStatus, ProcessStatus};
 use chrono::{Duration, Utc};
 #[cfg(feature = "sqlite")]
 use std::sync::{Arc, Barrier};
@@ -108,6 +108,27 @@ fn dedupe_preserves_later_file_hash_when_existing_row_has_none() {
     );
 }
 
+#[test]
+#[cfg(feature = "sqlite")]
+fn dedupe_takes_the_latest_event_kind() {
+    let m = fresh_manager("dedupe-event-kind");
+    let mut created = new_event("sonarr", "/media/kind.mkv", 30);
+    created.event_kind = EventKind::Created.into();
+    let first = m.add_event(&created).unwrap();
+    assert_eq!(first.kind(), EventKind::Created);
+
+    let mut deleted = new_event("notify", "/media/kind.mkv", 30);
+    deleted.event_kind = EventKind::Deleted.into();
+    let after = m.add_event(&deleted).unwrap();
+
+    assert_eq!(after.id, first.id, "same pending path should coalesce");
+    assert_eq!(
+        after.kind(),
+        EventKind::Deleted,
+        "a file removed before processing must be delivered as a delete"
+    );
+}
+
 #[test]
 #[cfg(feature = "sqlite")]
 fn concurrent_same_path_add_event_coalesces_without_unique_errors() {
@@ -178,6 +199,44 @@ fn postgres_upsert_conflict_target_matches_partial_index() {
     assert!(second.can_process > first.can_process);
 }
 
+#[test]
+#[cfg(feature = "mysql")]
+fn mysql_upsert_coalesces_queued_path_only() {
+    let Ok(url) = std::env::var("AUTOPULSE_TEST_MYSQL_URL") else {
+        return;
+    };
+
+    let pool = get_pool(&url).expect("mysql test database pool should initialize");
+    get_conn(&pool)
+        .expect("mysql test database connection should initialize")
+        .migrate()
+        .expect("mysql test database migrations should apply");
+
+    let mut settings = Settings::default();
+    settings.app.database_url = url;
+    let m = PulseManager::new(settings, pool);
+    let unique_path = format!(
+        "/media/mysql-{}.mkv",
+        Utc::now()
+            .timestamp_nanos_opt()
+            .expect("current timestamp should fit in nanos")
+    );
+
+    let first = m.add_event(&new_event("sonarr", &unique_path, 30)).unwrap();
+    let second = m.add_event(&new_event("notify", &unique_path, 60)).unwrap();
+
+    assert_eq!(first.id, second.id, "mysql upsert should coalesce");
+    assert!(second.can_process > first.can_process);
+
+    // Completed rows drop out of the generated unique key
+    let mut done = second.clone();
+    done.process_status = autopulse_database::models::ProcessStatus::Complete.into();
+    get_conn(&m.pool).unwrap().save_changes(&mut done).unwrap();
+
+    let third = m.add_event(&new_event("notify", &unique_path, 30)).unwrap();
+    assert_ne!(third.id, first.id, "a completed event is not reused");
+}
+
 #[test]
 #[cfg(feature = "sqlite")]
 fn retry_event_coalesces_with_new_arrival() {
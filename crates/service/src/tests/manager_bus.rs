@@ -0,0 +1,110 @@
+#[cfg(feature = "sqlite")]
+use crate::tests::util::fresh_manager;
+#[cfg(feature = "postgres")]
+use autopulse_database::conn::{get_conn, get_pool, Listener};
+#[cfg(feature = "sqlite")]
+use autopulse_database::models::NewScanEvent;
+
+#[test]
+#[cfg(feature = "sqlite")]
+fn relays_events_from_other_instances_only() {
+    let m = fresh_manager("manager-bus");
+    let ev = m
+        .add_event(&NewScanEvent {
+            event_source: "manual".to_string(),
+            file_path: "/media/bus.mkv".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let mut rx = m.subscribe();
+
+    let from = |instance: &str, id: &str| {
+        serde_json::json!({ "instance": instance, "id": id, "kind": "processed" }).to_string()
+    };
+
+    assert!(!m.relay(&from(&m.instance_id, &ev.id)).unwrap());
+    assert!(!m.relay(&from("other", "missing")).unwrap());
+    assert!(rx.try_recv().is_err());
+
+    assert!(m.relay(&from("other", &ev.id)).unwrap());
+    let relayed = rx.try_recv().unwrap();
+    assert_eq!(relayed.event.id, ev.id);
+    assert_eq!(
+        relayed.kind,
+        crate::settings::webhooks::EventType::Processed
+    );
+}
+
+#[test]
+#[cfg(feature = "postgres")]
+fn postgres_listener_receives_notifications() {
+    let Ok(url) = std::env::var("AUTOPULSE_TEST_POSTGRES_URL") else {
+        return;
+    };
+
+    let channel = format!("autopulse_test_{}", autopulse_utils::generate_uuid());
+    let mut listener = Listener::new(&url, &channel).unwrap();
+
+    let pool = get_pool(&url).expect("postgres test database pool should initialize");
+    get_conn(&pool).unwrap().notify(&channel, "hello").unwrap();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    let mut received = vec![];
+    while received.is_empty() && std::time::Instant::now() < deadline {
+        received = listener.drain().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    assert_eq!(received, vec!["hello".to_string()]);
+}
+
+#[tokio::test]
+#[cfg(feature = "postgres")]
+async fn postgres_bus_relays_events_published_by_another_instance() {
+    use crate::{manager::PulseManager, settings::Settings};
+    use autopulse_database::models::NewScanEvent;
+
+    let Ok(url) = std::env::var("AUTOPULSE_TEST_POSTGRES_URL") else {
+        return;
+    };
+
+    let pool = get_pool(&url).expect("postgres test database pool should initialize");
+    get_conn(&pool).unwrap().migrate().unwrap();
+
+    let mut settings = Settings::default();
+    settings.app.database_url = url;
+    settings.app.shared_bus = true;
+    let publisher = PulseManager::new(settings.clone(), pool.clone());
+    let relayer = std::sync::Arc::new(PulseManager::new(settings, pool));
+
+    let ev = publisher
+        .add_event(&NewScanEvent {
+            event_source: "manual".to_string(),
+            file_path: format!("/media/bus-{}.mkv", autopulse_utils::generate_uuid()),
+            ..Default::default()
+        })
+        .unwrap();
+    let mut rx = relayer.subscribe();
+
+    let bus = tokio::spawn({
+        let relayer = relayer.clone();
+        async move { relayer.start_bus().await }
+    });
+
+    // Publishes until the listener, connecting on its own thread, picks one up
+    let relayed = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            publisher.publish(crate::settings::webhooks::EventType::Processed, &ev);
+
+            match tokio::time::timeout(std::time::Duration::from_millis(500), rx.recv()).await {
+                Ok(relayed) => break relayed.unwrap(),
+                Err(_) => continue,
+            }
+        }
+    })
+    .await
+    .expect("the event should be relayed from the other instance");
+
+    assert_eq!(relayed.event.id, ev.id);
+    bus.abort();
+}
@@ -0,0 +1,66 @@
+use crate::settings::{triggers::Trigger, Settings};
+use crate::tests::util::fresh_manager_with;
+use autopulse_utils::generate_uuid;
+use notify_debouncer_full::notify::EventKind;
+use std::{env, fs};
+
+#[tokio::test]
+async fn notify_reconciles_changes_made_while_stopped() {
+    let root = env::temp_dir().join(generate_uuid());
+    fs::create_dir_all(root.join("season")).unwrap();
+    fs::write(root.join("season/old.mkv"), b"").unwrap();
+    fs::write(root.join("kept.mkv"), b"").unwrap();
+
+    let mut settings = Settings::default();
+    settings.triggers.insert(
+        "watch".to_string(),
+        serde_json::from_value(serde_json::json!({
+            "type": "notify",
+            "paths": [root.to_string_lossy()],
+        }))
+        .unwrap(),
+    );
+    let m = fresh_manager_with("manager-notify-snapshot", settings);
+    let Some(Trigger::Notify(trigger)) = m.settings.triggers.get("watch").cloned() else {
+        panic!("notify trigger should be configured");
+    };
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    assert_eq!(
+        m.reconcile_notify("watch", &trigger, tx.clone())
+            .await
+            .unwrap(),
+        0,
+        "the first run only records a baseline"
+    );
+    assert!(rx.try_recv().is_err());
+
+    fs::remove_file(root.join("season/old.mkv")).unwrap();
+    fs::write(root.join("season/new.mkv"), b"").unwrap();
+
+    assert_eq!(
+        m.reconcile_notify("watch", &trigger, tx.clone())
+            .await
+            .unwrap(),
+        2
+    );
+    let mut events = [rx.try_recv().unwrap(), rx.try_recv().unwrap()];
+    events.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(
+        events[0].path,
+        root.join("season/new.mkv").to_string_lossy().to_string()
+    );
+    assert!(matches!(events[0].kind, EventKind::Create(_)));
+    assert!(matches!(events[1].kind, EventKind::Remove(_)));
+
+    // A live event keeps the snapshot current, so the next start sees nothing new
+    fs::write(root.join("season/live.mkv"), b"").unwrap();
+    m.record_notify_path("watch", &root.join("season/live.mkv"))
+        .unwrap();
+    fs::remove_dir_all(root.join("season")).unwrap();
+    m.record_notify_path("watch", &root.join("season")).unwrap();
+
+    assert_eq!(m.reconcile_notify("watch", &trigger, tx).await.unwrap(), 0);
+
+    fs::remove_dir_all(&root).unwrap();
+}
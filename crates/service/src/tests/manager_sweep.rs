@@ -0,0 +1,53 @@
+use crate::settings::{triggers::Trigger, Settings};
+use crate::tests::util::fresh_manager_with;
+use autopulse_utils::generate_uuid;
+use std::{env, fs};
+
+#[tokio::test]
+async fn first_sweep_sets_a_baseline_and_later_sweeps_queue_new_files() {
+    let root = env::temp_dir().join(generate_uuid());
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("old.mkv"), b"").unwrap();
+
+    let mut settings = Settings::default();
+    settings.triggers.insert(
+        "nightly".to_string(),
+        serde_json::from_value(serde_json::json!({
+            "type": "sweep",
+            "schedule": "0 3 * * *",
+            "paths": [root.to_string_lossy()],
+        }))
+        .unwrap(),
+    );
+    let m = fresh_manager_with("manager-sweep", settings);
+    let Some(Trigger::Sweep(sweep)) = m.settings.triggers.get("nightly").cloned() else {
+        panic!("sweep trigger should be configured");
+    };
+
+    assert_eq!(m.last_sweep("nightly").unwrap(), None);
+    assert_eq!(m.sweep("nightly", &sweep).await.unwrap(), 0);
+    let baseline = m.last_sweep("nightly").unwrap().expect("baseline stored");
+
+    // Modified times only need to land after the baseline
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    fs::write(root.join("new.mkv"), b"").unwrap();
+
+    assert_eq!(m.sweep("nightly", &sweep).await.unwrap(), 1);
+    assert!(m.last_sweep("nightly").unwrap().unwrap() > baseline);
+
+    let events = m.get_events(10, 0, None, None, None).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].file_path,
+        root.join("new.mkv").to_string_lossy().to_string()
+    );
+    assert_eq!(events[0].event_source, "nightly");
+
+    assert_eq!(
+        m.sweep("nightly", &sweep).await.unwrap(),
+        0,
+        "unchanged files are not queued again"
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,65 @@
+use crate::{manager::PulseManager, settings::Settings};
+use autopulse_database::conn::{get_conn, get_pool};
+use autopulse_database::models::{FoundStatus, NewScanEvent};
+use chrono::{Duration, Utc};
+
+#[test]
+fn postgres_claims_are_exclusive_until_the_lease_lapses() {
+    let Ok(url) = std::env::var("AUTOPULSE_TEST_POSTGRES_URL") else {
+        return;
+    };
+
+    let pool = get_pool(&url).expect("postgres test database pool should initialize");
+    get_conn(&pool)
+        .expect("postgres test database connection should initialize")
+        .migrate()
+        .expect("postgres test database migrations should apply");
+
+    let mut settings = Settings::default();
+    settings.app.database_url = url;
+    let m = PulseManager::new(settings, pool);
+
+    // Its own source keeps other rows in the shared database out of the way
+    let source = format!("claims-{}", autopulse_utils::generate_uuid());
+    for n in 0..4 {
+        m.add_event(&NewScanEvent {
+            event_source: source.clone(),
+            file_path: format!("/media/{source}/{n}.mkv"),
+            found_status: FoundStatus::Found.into(),
+            can_process: Utc::now().naive_utc() - Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    let now = Utc::now().naive_utc();
+    let mine = |evs: Vec<autopulse_database::models::ScanEvent>| {
+        evs.into_iter()
+            .filter(|ev| ev.event_source == source)
+            .collect::<Vec<_>>()
+    };
+    let claim = |instance: &str, at, until| {
+        mine(
+            get_conn(&m.pool)
+                .unwrap()
+                .claim_due_events(instance, at, until, true, 10_000)
+                .unwrap(),
+        )
+    };
+
+    let first = claim("a", now, now + Duration::minutes(10));
+    assert_eq!(first.len(), 4);
+    assert!(first.iter().all(|ev| ev.claimed_by.as_deref() == Some("a")));
+
+    assert!(
+        claim("b", now, now + Duration::minutes(10)).is_empty(),
+        "leased events are not handed to another instance"
+    );
+
+    let later = now + Duration::minutes(11);
+    let reclaimed = claim("b", later, later + Duration::minutes(10));
+    assert_eq!(reclaimed.len(), 4, "expired leases are reclaimed");
+    assert!(reclaimed
+        .iter()
+        .all(|ev| ev.claimed_by.as_deref() == Some("b")));
+}
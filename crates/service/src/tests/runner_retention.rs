@@ -0,0 +1,132 @@
+use crate::archive::archive_path;
+use crate::runner::PulseRunner;
+use crate::settings::Settings;
+use crate::tests::util::fresh_manager_with;
+use autopulse_database::conn::get_conn;
+use autopulse_database::models::{FoundStatus, NewScanEvent, ProcessStatus};
+use chrono::{Duration, Utc};
+use flate2::read::MultiGzDecoder;
+use std::io::{BufRead, BufReader};
+
+fn add_aged(m: &crate::manager::PulseManager, path: &str, status: ProcessStatus, age_days: i64) {
+    let mut ev = m
+        .add_event(&NewScanEvent {
+            file_path: path.to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    ev.process_status = status.into();
+    ev.created_at = Utc::now().naive_utc() - Duration::days(age_days);
+    get_conn(&m.pool).unwrap().save_changes(&mut ev).unwrap();
+}
+
+#[tokio::test]
+async fn cleanup_archives_expired_events_and_keeps_their_counts() {
+    let archive = tempfile::tempdir().unwrap();
+    let mut settings = Settings::default();
+    settings.opts.retention.complete = Some(1);
+    settings.opts.retention.archive = Some(archive.path().to_path_buf());
+    settings.opts.retention.batch_size = 2;
+    let m = fresh_manager_with("runner-retention", settings);
+
+    for i in 0..5 {
+        add_aged(
+            &m,
+            &format!("/media/old-{i}.mkv"),
+            ProcessStatus::Complete,
+            3,
+        );
+    }
+    add_aged(&m, "/media/new.mkv", ProcessStatus::Complete, 0);
+    add_aged(&m, "/media/waiting.mkv", ProcessStatus::Pending, 3);
+
+    PulseRunner::new(&m).cleanup().unwrap();
+
+    let left = m
+        .get_events(10, 1, None, None, None)
+        .unwrap()
+        .into_iter()
+        .map(|ev| ev.file_path)
+        .collect::<Vec<_>>();
+    assert_eq!(left.len(), 2, "only expired events are pruned: {left:?}");
+    assert!(left.contains(&"/media/new.mkv".to_string()));
+    assert!(left.contains(&"/media/waiting.mkv".to_string()));
+
+    let file = std::fs::File::open(archive_path(archive.path(), Utc::now().date_naive()))
+        .expect("expired events should be archived");
+    let archived = BufReader::new(MultiGzDecoder::new(file))
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(&line.unwrap()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(archived.len(), 5, "every batch is appended to the archive");
+    assert!(archived.iter().all(|ev| ev["process_status"] == "complete"));
+
+    let stats = m.get_stats().unwrap();
+    assert_eq!(stats.total, 7, "pruned events still count");
+    assert_eq!(stats.processed, 6);
+    assert_eq!(stats.pending, 1);
+    assert_eq!(stats.expired, 0);
+}
+
+#[tokio::test]
+async fn cleanup_waits_between_passes() {
+    let mut settings = Settings::default();
+    settings.opts.retention.complete = Some(1);
+    let m = fresh_manager_with("runner-retention-interval", settings);
+    let mut runner = PulseRunner::new(&m);
+
+    runner.cleanup().unwrap();
+    add_aged(&m, "/media/old.mkv", ProcessStatus::Complete, 3);
+    runner.cleanup().unwrap();
+
+    assert_eq!(
+        m.get_stats().unwrap().total,
+        1,
+        "the next pass only runs after the cleanup interval"
+    );
+}
+
+#[tokio::test]
+async fn cleanup_with_default_opts_prunes_old_processed_events() {
+    let m = fresh_manager_with("runner-retention-default", Settings::default());
+
+    // Without `check_path` processed events are never marked found
+    add_aged(&m, "/media/old.mkv", ProcessStatus::Complete, 11);
+    add_aged(&m, "/media/new.mkv", ProcessStatus::Complete, 1);
+    add_aged(&m, "/media/never.mkv", ProcessStatus::Pending, 11);
+
+    let mut found = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/found.mkv".to_string(),
+            found_status: FoundStatus::Found.into(),
+            ..Default::default()
+        })
+        .unwrap();
+    found.process_status = ProcessStatus::Complete.into();
+    found.created_at = Utc::now().naive_utc() - Duration::days(11);
+    get_conn(&m.pool).unwrap().save_changes(&mut found).unwrap();
+
+    PulseRunner::new(&m).cleanup().unwrap();
+
+    let mut left = m
+        .get_events(10, 1, None, None, None)
+        .unwrap()
+        .into_iter()
+        .map(|ev| ev.file_path)
+        .collect::<Vec<_>>();
+    left.sort();
+    assert_eq!(
+        left,
+        vec!["/media/found.mkv", "/media/new.mkv"],
+        "processed events past `cleanup_days` that were never found are pruned"
+    );
+
+    let stats = m.get_stats().unwrap();
+    assert_eq!(stats.total, 4);
+    assert_eq!(stats.processed, 3);
+    assert_eq!(stats.expired, 1, "the pending event was pruned unprocessed");
+    assert_eq!(
+        stats.pending + stats.retrying + stats.processed + stats.failed + stats.expired,
+        stats.total
+    );
+}
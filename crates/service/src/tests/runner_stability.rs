@@ -0,0 +1,79 @@
+use crate::runner::PulseRunner;
+use crate::settings::{opts::Stability, Settings};
+use crate::tests::util::fresh_manager_with;
+use autopulse_database::models::{FoundStatus, NewScanEvent};
+use autopulse_utils::generate_uuid;
+use std::env;
+
+#[tokio::test]
+async fn growing_file_is_unstable_until_it_settles() {
+    let mut settings = Settings::default();
+    settings.opts.check_path = true;
+    settings.opts.stability = Some(Stability {
+        checks: 2,
+        interval: 0,
+        quiet: None,
+    });
+    let m = fresh_manager_with("runner-stability", settings);
+
+    let path = env::temp_dir().join(generate_uuid());
+    std::fs::write(&path, b"part").unwrap();
+
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let runner = PulseRunner::new(&m);
+    let status = |id: &String| m.get_event(id).unwrap().unwrap().found_status;
+
+    runner.update_found_status().await.unwrap();
+    assert_eq!(status(&ev.id), String::from(FoundStatus::Unstable));
+
+    std::fs::write(&path, b"partial copy").unwrap();
+    runner.update_found_status().await.unwrap();
+    assert_eq!(
+        status(&ev.id),
+        String::from(FoundStatus::Unstable),
+        "a size change starts counting again"
+    );
+
+    runner.update_found_status().await.unwrap();
+    let ev = m.get_event(&ev.id).unwrap().unwrap();
+    assert_eq!(ev.found_status, String::from(FoundStatus::Found));
+    assert!(ev.found_at.is_some());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn unstable_file_that_is_deleted_is_not_found_again() {
+    let mut settings = Settings::default();
+    settings.opts.check_path = true;
+    settings.opts.stability = Some(Stability {
+        checks: 3,
+        interval: 0,
+        quiet: None,
+    });
+    let m = fresh_manager_with("runner-stability-deleted", settings);
+
+    let path = env::temp_dir().join(generate_uuid());
+    std::fs::write(&path, b"part").unwrap();
+
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let runner = PulseRunner::new(&m);
+    let status = |id: &String| m.get_event(id).unwrap().unwrap().found_status;
+
+    runner.update_found_status().await.unwrap();
+    assert_eq!(status(&ev.id), String::from(FoundStatus::Unstable));
+
+    std::fs::remove_file(&path).unwrap();
+    runner.update_found_status().await.unwrap();
+    assert_eq!(status(&ev.id), String::from(FoundStatus::NotFound));
+}
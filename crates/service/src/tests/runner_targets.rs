@@ -0,0 +1,519 @@
+use crate::circuit::CircuitState;
+use crate::runner::PulseRunner;
+use crate::settings::{targets::Target, Settings};
+use crate::tests::util::fresh_manager_with;
+use autopulse_database::models::{FoundStatus, NewScanEvent, ProcessStatus, ScanEventTarget};
+
+fn command(raw: &str) -> Target {
+    serde_json::from_value(serde_json::json!({ "type": "command", "raw": raw })).unwrap()
+}
+
+fn settings() -> Settings {
+    let mut settings = Settings::default();
+    settings.opts.max_retries = 2;
+    settings.targets.insert("good".to_string(), command("true"));
+    settings.targets.insert("bad".to_string(), command("false"));
+    settings
+}
+
+fn find<'a>(targets: &'a [ScanEventTarget], name: &str) -> &'a ScanEventTarget {
+    targets
+        .iter()
+        .find(|t| t.target == name)
+        .unwrap_or_else(|| panic!("missing delivery for '{name}'"))
+}
+
+#[tokio::test]
+async fn failing_target_retries_without_redelivering_to_others() {
+    let m = fresh_manager_with("runner-targets-retry", settings());
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/movie.mkv".to_string(),
+            found_status: FoundStatus::Found.into(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let targets = m.get_event_targets(&ev.id).unwrap();
+    let good = find(&targets, "good");
+    let bad = find(&targets, "bad");
+    assert_eq!(good.status, String::from(ProcessStatus::Complete));
+    assert_eq!(good.attempts, 1);
+    assert_eq!(bad.status, String::from(ProcessStatus::Retry));
+    assert_eq!(bad.attempts, 1);
+    assert!(bad.next_retry_at.is_some());
+    assert!(
+        bad.last_error.is_some(),
+        "failed delivery should keep its error"
+    );
+
+    let ev = m.get_event(&ev.id).unwrap().unwrap();
+    assert_eq!(ev.process_status, String::from(ProcessStatus::Retry));
+    assert_eq!(ev.next_retry_at, bad.next_retry_at);
+
+    // Pull the retry forward instead of waiting out the backoff.
+    m.reschedule_event(&ev.id).unwrap();
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let targets = m.get_event_targets(&ev.id).unwrap();
+    assert_eq!(find(&targets, "good").attempts, 1, "good must not rerun");
+    let bad = find(&targets, "bad");
+    assert_eq!(bad.status, String::from(ProcessStatus::Failed));
+    assert_eq!(bad.attempts, 2);
+
+    let ev = m.get_event(&ev.id).unwrap().unwrap();
+    assert_eq!(ev.process_status, String::from(ProcessStatus::Failed));
+
+    let attempts = m.get_event_attempts(&ev.id).unwrap();
+    assert_eq!(attempts.len(), 3, "good once, bad twice");
+    assert!(attempts
+        .iter()
+        .filter(|a| a.target == "bad")
+        .all(|a| !a.success
+            && a.error
+                .as_deref()
+                .is_some_and(|e| e.contains("exit status"))));
+    assert!(attempts
+        .iter()
+        .any(|a| a.target == "good" && a.success && a.error.is_none()));
+}
+
+#[tokio::test]
+async fn rescheduling_complete_event_clears_deliveries() {
+    let mut settings = Settings::default();
+    settings.targets.insert("good".to_string(), command("true"));
+    let m = fresh_manager_with("runner-targets-complete", settings);
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/show.mkv".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let ev = m.get_event(&ev.id).unwrap().unwrap();
+    assert_eq!(ev.process_status, String::from(ProcessStatus::Complete));
+    assert_eq!(m.get_event_targets(&ev.id).unwrap().len(), 1);
+
+    m.reschedule_event(&ev.id).unwrap();
+    assert!(m.get_event_targets(&ev.id).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn hung_target_times_out_without_holding_up_others() {
+    let mut settings = Settings::default();
+    settings.opts.target_timeout = 1;
+    settings.targets.insert("good".to_string(), command("true"));
+    settings
+        .targets
+        .insert("hung".to_string(), command("sleep 30"));
+    let m = fresh_manager_with("runner-targets-timeout", settings);
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/movie.mkv".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    let targets = m.get_event_targets(&ev.id).unwrap();
+    assert_eq!(
+        find(&targets, "good").status,
+        String::from(ProcessStatus::Complete)
+    );
+    let hung = find(&targets, "hung");
+    assert_eq!(hung.status, String::from(ProcessStatus::Retry));
+    assert!(hung
+        .last_error
+        .as_deref()
+        .is_some_and(|e| e.contains("timed out")));
+}
+
+#[tokio::test]
+async fn hung_target_holds_the_pass_for_one_timeout_only() {
+    let mut settings = Settings::default();
+    settings.opts.target_timeout = 1;
+    settings
+        .targets
+        .insert("hung".to_string(), command("sleep 30"));
+    settings
+        .targets
+        .insert("quick".to_string(), command("true"));
+    settings.targets.insert(
+        "dependent".to_string(),
+        serde_json::from_value(serde_json::json!({
+            "type": "command",
+            "raw": "true",
+            "after": ["quick"],
+        }))
+        .unwrap(),
+    );
+    // Routed events go to `hung` in a group of their own
+    settings.routes = serde_json::from_value(serde_json::json!([{
+        "path": "^/media/tv/",
+        "targets": ["hung"],
+        "rewrite": { "from": "^/media", "to": "/data" },
+    }]))
+    .unwrap();
+    settings.normalize().unwrap();
+
+    let m = fresh_manager_with("runner-targets-deadline", settings);
+    let add = |path: &str| {
+        m.add_event(&NewScanEvent {
+            file_path: path.to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap()
+    };
+    let routed = add("/media/tv/Show/ep.mkv");
+    let unrouted = add("/downloads/ep.mkv");
+
+    let started = std::time::Instant::now();
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+    assert!(
+        started.elapsed() < std::time::Duration::from_millis(1900),
+        "groups share one timeout and the pass ends with it, took {:?}",
+        started.elapsed()
+    );
+
+    for ev in [&routed, &unrouted] {
+        let targets = m.get_event_targets(&ev.id).unwrap();
+        assert!(find(&targets, "hung")
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("timed out")));
+    }
+
+    let targets = m.get_event_targets(&unrouted.id).unwrap();
+    assert_eq!(
+        find(&targets, "quick").status,
+        String::from(ProcessStatus::Complete)
+    );
+    assert!(
+        targets.iter().all(|t| t.target != "dependent"),
+        "a stage that no longer fits in the pass is not sent the event"
+    );
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let targets = m.get_event_targets(&unrouted.id).unwrap();
+    let dependent = find(&targets, "dependent");
+    assert_eq!(dependent.status, String::from(ProcessStatus::Complete));
+    assert_eq!(dependent.attempts, 1, "being deferred is not an attempt");
+}
+
+/// A Subsonic server whose scan never finishes, returning its URL.
+fn always_scanning_subsonic() -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        let body = r#"{"subsonic-response":{"status":"ok","scanStatus":{"scanning":true}}}"#;
+
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+
+    url
+}
+
+#[tokio::test]
+async fn subsonic_scan_timeout_outlasts_target_timeout() {
+    let mut settings = Settings::default();
+    settings.opts.target_timeout = 1;
+    settings.targets.insert(
+        "navidrome".to_string(),
+        serde_json::from_value(serde_json::json!({
+            "type": "subsonic",
+            "url": always_scanning_subsonic(),
+            "username": "admin",
+            "password": "password",
+            "scan_timeout": 2,
+            "poll_interval": 1,
+        }))
+        .unwrap(),
+    );
+    let m = fresh_manager_with("runner-targets-subsonic", settings);
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: "/music/album/track.flac".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+    assert!(
+        started.elapsed() >= std::time::Duration::from_secs(2),
+        "the scan should be waited on for its own scan_timeout"
+    );
+
+    // A scan still running at `scan_timeout` counts as done, not as a timed out target
+    let targets = m.get_event_targets(&ev.id).unwrap();
+    let navidrome = find(&targets, "navidrome");
+    assert_eq!(navidrome.status, String::from(ProcessStatus::Complete));
+    assert!(navidrome.last_error.is_none());
+}
+
+#[tokio::test]
+async fn concurrency_limit_splits_the_batch_and_merges_results() {
+    let mut settings = Settings::default();
+    settings.targets.insert(
+        "split".to_string(),
+        serde_json::from_value(serde_json::json!({
+            "type": "command",
+            "raw": r#"[ "$FILE_PATH" = /media/a.mkv ]"#,
+            "concurrency": 2,
+        }))
+        .unwrap(),
+    );
+    let m = fresh_manager_with("runner-targets-concurrency", settings);
+    let add = |path: &str| {
+        m.add_event(&NewScanEvent {
+            file_path: path.to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap()
+    };
+    let a = add("/media/a.mkv");
+    let b = add("/media/b.mkv");
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let a = m.get_event(&a.id).unwrap().unwrap();
+    let b = m.get_event(&b.id).unwrap().unwrap();
+    assert_eq!(a.process_status, String::from(ProcessStatus::Complete));
+    assert_eq!(b.process_status, String::from(ProcessStatus::Retry));
+}
+
+#[tokio::test]
+async fn open_circuit_pauses_dispatch_without_burning_retries() {
+    let mut settings = settings();
+    settings.opts.max_retries = 5;
+    settings.opts.circuit_threshold = 1;
+    let m = fresh_manager_with("runner-targets-circuit", settings);
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/movie.mkv".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    assert_eq!(m.circuits.health("bad").state, CircuitState::Open);
+    assert_eq!(m.circuits.health("good").state, CircuitState::Closed);
+
+    m.reschedule_event(&ev.id).unwrap();
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let targets = m.get_event_targets(&ev.id).unwrap();
+    assert_eq!(
+        find(&targets, "bad").attempts,
+        1,
+        "paused target must not run"
+    );
+
+    let ev = m.get_event(&ev.id).unwrap().unwrap();
+    assert_eq!(ev.process_status, String::from(ProcessStatus::Retry));
+    assert_eq!(ev.failed_times, 1, "a pause is not a failure");
+    assert_eq!(ev.next_retry_at, m.circuits.health("bad").retry_at);
+}
+
+#[tokio::test]
+async fn after_waits_for_prerequisites_to_complete_the_event() {
+    let marker = std::env::temp_dir().join(format!(
+        "autopulse-runner-after-{}",
+        autopulse_utils::generate_uuid()
+    ));
+    let marker = marker.display();
+
+    let after = |raw: String, after: &[&str]| -> Target {
+        serde_json::from_value(serde_json::json!({
+            "type": "command",
+            "raw": raw,
+            "after": after,
+        }))
+        .unwrap()
+    };
+
+    let mut settings = settings();
+    // Named so that sorting alone would run the dependent first
+    settings.targets.insert(
+        "a_scan".to_string(),
+        after(format!("test -f {marker}"), &["z_refresh"]),
+    );
+    settings
+        .targets
+        .insert("z_refresh".to_string(), command(&format!("touch {marker}")));
+    settings
+        .targets
+        .insert("blocked".to_string(), after("true".to_string(), &["bad"]));
+    let m = fresh_manager_with("runner-targets-after", settings);
+    let ev = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/movie.mkv".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let targets = m.get_event_targets(&ev.id).unwrap();
+    assert_eq!(
+        find(&targets, "z_refresh").status,
+        String::from(ProcessStatus::Complete)
+    );
+    assert_eq!(
+        find(&targets, "a_scan").status,
+        String::from(ProcessStatus::Complete),
+        "dependent should run in the same tick, after its prerequisite"
+    );
+    assert!(
+        targets.iter().all(|t| t.target != "blocked"),
+        "dependent of a failing target must not be sent the event"
+    );
+
+    let ev = m.get_event(&ev.id).unwrap().unwrap();
+    assert_eq!(ev.process_status, String::from(ProcessStatus::Retry));
+
+    std::fs::remove_file(marker.to_string()).ok();
+}
+
+#[tokio::test]
+async fn routes_pick_targets_from_captures_and_override_their_rewrite() {
+    let mut settings = Settings::default();
+    settings.targets.insert(
+        "plex_tv".to_string(),
+        command(r#"test "$FILE_PATH" = /data/tv/Show/ep.mkv"#),
+    );
+    settings
+        .targets
+        .insert("plex_movies".to_string(), command("true"));
+    settings
+        .targets
+        .insert("jellyfin".to_string(), command("true"));
+    settings.routes = serde_json::from_value(serde_json::json!([{
+        "path": "^/media/(?P<library>tv|movies)/",
+        "targets": ["plex_{library}"],
+        "rewrite": { "from": "^/media", "to": "/data" },
+    }]))
+    .unwrap();
+    settings.normalize().unwrap();
+
+    let m = fresh_manager_with("runner-targets-routes", settings);
+    let routed = m
+        .add_event(&NewScanEvent {
+            file_path: "/media/tv/Show/ep.mkv".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+    let unrouted = m
+        .add_event(&NewScanEvent {
+            file_path: "/downloads/ep.mkv".to_string(),
+            can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let targets = m.get_event_targets(&routed.id).unwrap();
+    assert_eq!(targets.len(), 1, "only the captured target applies");
+    assert_eq!(
+        find(&targets, "plex_tv").status,
+        String::from(ProcessStatus::Complete),
+        "target should see the route's rewrite"
+    );
+
+    let targets = m.get_event_targets(&unrouted.id).unwrap();
+    assert_eq!(targets.len(), 3, "unrouted events go to every target");
+}
+
+#[tokio::test]
+async fn coalescing_target_scans_a_settled_directory_once() {
+    let log = std::env::temp_dir().join(format!(
+        "autopulse-runner-coalesce-{}",
+        autopulse_utils::generate_uuid()
+    ));
+
+    let coalescing = |window: u64| -> Target {
+        serde_json::from_value(serde_json::json!({
+            "type": "command",
+            "raw": format!("echo \"$FILE_PATH\" >> {}", log.display()),
+            "coalesce": window,
+        }))
+        .unwrap()
+    };
+
+    let mut settings = Settings::default();
+    settings.targets.insert("now".to_string(), coalescing(0));
+    settings
+        .targets
+        .insert("later".to_string(), coalescing(3600));
+    let m = fresh_manager_with("runner-targets-coalesce", settings);
+
+    let mut ids = vec![];
+    for episode in ["e01.mkv", "e02.mkv", "e03.mkv"] {
+        let ev = m
+            .add_event(&NewScanEvent {
+                file_path: format!("/media/tv/Show/Season 1/{episode}"),
+                can_process: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1),
+                ..Default::default()
+            })
+            .unwrap();
+        ids.push(ev.id);
+    }
+
+    PulseRunner::new(&m).update_process_status().await.unwrap();
+
+    let scanned = std::fs::read_to_string(&log).unwrap();
+    assert_eq!(
+        scanned.lines().collect::<Vec<_>>(),
+        vec!["/media/tv/Show/Season 1"]
+    );
+
+    for id in &ids {
+        let targets = m.get_event_targets(id).unwrap();
+        assert_eq!(
+            find(&targets, "now").status,
+            String::from(ProcessStatus::Complete)
+        );
+        assert!(
+            targets.iter().all(|t| t.target != "later"),
+            "an unsettled directory must not be sent"
+        );
+
+        let ev = m.get_event(id).unwrap().unwrap();
+        assert_eq!(ev.process_status, String::from(ProcessStatus::Pending));
+        assert!(ev
+            .next_retry_at
+            .is_some_and(|at| at > chrono::Utc::now().naive_utc()));
+    }
+
+    std::fs::remove_file(&log).ok();
+}
@@ -0,0 +1,71 @@
+use crate::runner::PulseRunner;
+use crate::settings::Settings;
+use crate::tests::util::{fresh_manager, fresh_manager_with};
+use autopulse_database::models::NewScanEvent;
+use chrono::{Duration, Utc};
+
+fn event(path: &str, can_process_secs: i64) -> NewScanEvent {
+    NewScanEvent {
+        file_path: path.to_string(),
+        can_process: Utc::now().naive_utc() + Duration::seconds(can_process_secs),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn add_event_wakes_the_runner() {
+    let m = fresh_manager("runner-wake-add");
+    m.add_event(&event("/media/a.mkv", 0)).unwrap();
+
+    tokio::time::timeout(std::time::Duration::from_secs(1), m.wake.notified())
+        .await
+        .expect("add_event should leave a wake-up for the runner");
+}
+
+#[tokio::test]
+async fn next_wake_is_the_earliest_upcoming_event() {
+    let m = fresh_manager("runner-wake-next");
+    let runner = PulseRunner::new(&m);
+
+    assert_eq!(runner.next_wake().unwrap(), None, "nothing queued");
+
+    let later = m.add_event(&event("/media/later.mkv", 120)).unwrap();
+    let sooner = m.add_event(&event("/media/sooner.mkv", 60)).unwrap();
+    assert!(later.can_process > sooner.can_process);
+
+    assert_eq!(runner.next_wake().unwrap(), Some(sooner.can_process));
+
+    let now = Utc::now().naive_utc();
+    m.add_event(&event("/media/due.mkv", -1)).unwrap();
+
+    let wake = runner
+        .next_wake()
+        .unwrap()
+        .expect("a due event needs a run");
+    assert!(
+        wake >= now && wake < sooner.can_process,
+        "due events run now"
+    );
+}
+
+#[tokio::test]
+async fn missing_files_back_off_instead_of_waking_every_second() {
+    let mut settings = Settings::default();
+    settings.opts.check_path = true;
+    settings.opts.poll_interval = 30;
+    let m = fresh_manager_with("runner-wake-missing", settings);
+    let runner = PulseRunner::new(&m);
+
+    m.add_event(&event("/media/missing.mkv", -600)).unwrap();
+
+    let now = Utc::now().naive_utc();
+    let wake = runner
+        .next_wake()
+        .unwrap()
+        .expect("missing files are rechecked");
+    assert!(
+        wake - now >= Duration::seconds(29),
+        "backoff is capped by the poll interval"
+    );
+    assert!(wake - now <= Duration::seconds(31));
+}
@@ -0,0 +1,26 @@
+use super::{http_stub, path_filter::event};
+use crate::settings::targets::{kavita::Kavita, ProcessError, TargetProcess};
+
+fn kavita(addr: &str) -> Kavita {
+    serde_json::from_value(serde_json::json!({
+        "url": format!("http://{addr}"),
+        "token": "kavita-api-key",
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn failed_login_does_not_store_the_api_key() {
+    let ev = event("/books/a.epub");
+
+    for addr in [
+        http_stub("401 Unauthorized", "nope"),
+        http_stub("200 OK", "not json"),
+    ] {
+        let e = kavita(&addr).process(&[&ev]).await.unwrap_err();
+        let err = ProcessError::from(&e);
+
+        assert!(!err.message.contains("kavita-api-key"), "{}", err.message);
+        assert!(!format!("{e:?}").contains("kavita-api-key"), "{e:?}");
+    }
+}
@@ -1 +1,26 @@
+pub mod kavita;
 pub mod path_filter;
+pub mod process_result;
+pub mod subsonic;
+
+/// Answers every request on a local port with `status` and `body`, returns the address.
+pub fn http_stub(status: &'static str, body: &'static str) -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+
+    addr.to_string()
+}
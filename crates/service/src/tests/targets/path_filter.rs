@@ -1,7 +1,7 @@
 use crate::settings::targets::Target;
-use autopulse_database::models::{FoundStatus, ProcessStatus, ScanEvent};
+use autopulse_database::models::{EventKind, FoundStatus, ProcessStatus, ScanEvent};
 
-fn event(path: &str) -> ScanEvent {
+pub(super) fn event(path: &str) -> ScanEvent {
     let now = chrono::Utc::now().naive_utc();
 
     ScanEvent {
@@ -14,12 +14,15 @@ fn event(path: &str) -> ScanEvent {
         found_status: FoundStatus::Found.into(),
         failed_times: 0,
         next_retry_at: None,
-        targets_hit: String::new(),
         found_at: Some(now),
         processed_at: None,
         created_at: now,
         updated_at: now,
         can_process: now,
+        event_kind: EventKind::Modified.into(),
+        previous_path: None,
+        claimed_by: None,
+        claimed_until: None,
     }
 }
 
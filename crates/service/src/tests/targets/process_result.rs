@@ -0,0 +1,69 @@
+use crate::settings::targets::ProcessResult;
+
+#[test]
+fn failure_wins_over_success() {
+    let mut result = ProcessResult::default();
+    result.succeed("a");
+    result.fail("a", &anyhow::anyhow!("refresh rejected"));
+
+    let err = result.outcome("a").expect_err("failure should win");
+    assert_eq!(err.message, "refresh rejected");
+    assert_eq!(err.status, None);
+}
+
+#[test]
+fn unreported_event_is_a_failure() {
+    let result = ProcessResult::from(vec!["a".to_string()]);
+
+    assert!(result.outcome("a").is_ok());
+    assert!(result.outcome("b").is_err());
+}
+
+#[test]
+fn failure_keeps_the_whole_error_chain() {
+    let mut result = ProcessResult::default();
+    let e = anyhow::anyhow!("connection refused").context("failed to get libraries");
+    result.fail("a", &e);
+
+    assert_eq!(
+        result.outcome("a").unwrap_err().message,
+        "failed to get libraries: connection refused"
+    );
+}
+
+#[tokio::test]
+async fn failure_does_not_store_credentials_from_the_url() {
+    use crate::settings::targets::{ProcessError, RequestBuilderPerform};
+
+    let addr = super::http_stub("401 Unauthorized", "nope");
+    let e = reqwest::Client::new()
+        .get(format!(
+            "http://admin:hunter2@{addr}/api/library?apiKey=secret"
+        ))
+        .perform()
+        .await
+        .unwrap_err();
+    let err = ProcessError::from(&e);
+
+    assert_eq!(err.status, Some(401));
+    assert!(err.message.contains("/api/library"), "{}", err.message);
+    assert!(!err.message.contains("secret"), "{}", err.message);
+    assert!(!err.message.contains("hunter2"), "{}", err.message);
+
+    // Nothing listens on the port once the listener is dropped
+    let closed = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let client = reqwest::Client::new();
+    let url = format!("http://{closed}/api/library?apiKey=secret");
+
+    for e in [
+        client.get(&url).perform().await.unwrap_err(),
+        anyhow::Error::from(client.get(&url).send().await.unwrap_err())
+            .context("failed to get libraries"),
+    ] {
+        let message = ProcessError::from(&e).message;
+        assert!(!message.contains("secret"), "{message}");
+    }
+}
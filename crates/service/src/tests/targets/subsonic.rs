@@ -0,0 +1,26 @@
+use super::{http_stub, path_filter::event};
+use crate::settings::targets::{subsonic::Subsonic, TargetProcess};
+
+#[tokio::test]
+async fn failed_scan_does_not_store_the_credentials() {
+    let ev = event("/music/a.flac");
+
+    for addr in [
+        http_stub("401 Unauthorized", "nope"),
+        http_stub("200 OK", "not json"),
+    ] {
+        let subsonic: Subsonic = serde_json::from_value(serde_json::json!({
+            "url": format!("http://{addr}"),
+            "username": "navidrome-admin",
+            "password": "sesame",
+        }))
+        .unwrap();
+
+        let result = subsonic.process(&[&ev]).await.unwrap();
+        let message = result.outcome(&ev.id).unwrap_err().message;
+
+        for secret in ["navidrome-admin", "t=", "s="] {
+            assert!(!message.contains(secret), "{message}");
+        }
+    }
+}
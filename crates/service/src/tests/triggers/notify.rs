@@ -1,9 +1,9 @@
 #![cfg(test)]
 mod tests {
-    use crate::settings::triggers::notify::Notify;
+    use crate::settings::triggers::notify::{Notify, NotifyEvent};
     use autopulse_utils::generate_uuid;
     use notify_debouncer_full::notify::{
-        event::{AccessKind, AccessMode, CreateKind},
+        event::{AccessKind, AccessMode, CreateKind, ModifyKind, RenameMode},
         EventKind,
     };
     use std::{env, fs::create_dir, io::Write, time::Duration};
@@ -92,9 +92,9 @@ mod tests {
         );
 
         // Verify that we got either a Create or a CloseWrite event
-        let has_expected_event = result.iter().any(|(_, kind)| {
+        let has_expected_event = result.iter().any(|ev| {
             matches!(
-                kind,
+                ev.kind,
                 EventKind::Create(_) | EventKind::Access(AccessKind::Close(AccessMode::Write))
             )
         });
@@ -194,9 +194,9 @@ mod tests {
         .await?;
 
         // None of the forwarded events should be Modify(Data(_))
-        let has_modify_data = result.iter().any(|(_, kind)| {
+        let has_modify_data = result.iter().any(|ev| {
             matches!(
-                kind,
+                ev.kind,
                 EventKind::Modify(notify_debouncer_full::notify::event::ModifyKind::Data(_))
             )
         });
@@ -242,7 +242,7 @@ mod tests {
         // Should have a Remove event for the deleted file
         let has_remove = result
             .iter()
-            .any(|(_, kind)| matches!(kind, EventKind::Remove(_)));
+            .any(|ev| matches!(ev.kind, EventKind::Remove(_)));
         assert!(
             has_remove,
             "expected a Remove event after file deletion, got: {result:?}"
@@ -251,4 +251,91 @@ mod tests {
         watcher_task.abort();
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_watcher_keeps_previous_path_of_renames() -> anyhow::Result<()> {
+        let path = env::temp_dir().join(generate_uuid());
+        create_dir(&path)?;
+        let old = path.join("old.mkv");
+        let new = path.join("new.mkv");
+        std::fs::write(&old, b"episode")?;
+
+        let notifier = test_notifier(&path, 1);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let notifier_clone = notifier.clone();
+        let watcher_task = tokio::spawn(async move { notifier_clone.watcher(tx).await });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::fs::rename(&old, &new)?;
+
+        let result = timeout(Duration::from_secs(5), async {
+            let mut events = vec![];
+            while let Ok(Some(event)) =
+                tokio::time::timeout(Duration::from_secs(3), rx.recv()).await
+            {
+                events.push(event);
+            }
+            events
+        })
+        .await?;
+
+        let renamed = result
+            .iter()
+            .find(|ev| ev.path == new.to_string_lossy())
+            .expect("expected an event for the new path");
+        assert_eq!(
+            renamed.kind,
+            EventKind::Modify(ModifyKind::Name(RenameMode::To))
+        );
+        assert_eq!(
+            renamed.previous_path.as_deref(),
+            Some(old.to_string_lossy().as_ref()),
+            "the new path should keep the path it was renamed from"
+        );
+
+        watcher_task.abort();
+        std::fs::remove_dir_all(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconcile_sends_changes_between_snapshots() -> anyhow::Result<()> {
+        let path = env::temp_dir().join(generate_uuid());
+        create_dir(&path)?;
+        std::fs::write(path.join("kept.mkv"), b"same")?;
+        std::fs::write(path.join("grown.mkv"), b"a")?;
+        std::fs::write(path.join("removed.mkv"), b"gone")?;
+
+        let notifier = test_notifier(&path, 1);
+        let previous = notifier.snapshot();
+        assert_eq!(previous.len(), 3);
+
+        std::fs::write(path.join("grown.mkv"), b"a bit longer")?;
+        std::fs::remove_file(path.join("removed.mkv"))?;
+        std::fs::write(path.join("added.mkv"), b"new")?;
+        let current = notifier.snapshot();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        assert_eq!(notifier.reconcile(tx, &previous, &current)?, 3);
+
+        let mut events = vec![];
+        while let Ok(NotifyEvent {
+            path: file, kind, ..
+        }) = rx.try_recv()
+        {
+            let name = std::path::Path::new(&file).file_name().unwrap().to_owned();
+            events.push((name.to_string_lossy().to_string(), kind));
+        }
+        events.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert!(matches!(&events[..], [
+            (added, EventKind::Create(_)),
+            (grown, EventKind::Modify(_)),
+            (removed, EventKind::Remove(_)),
+        ] if added == "added.mkv" && grown == "grown.mkv" && removed == "removed.mkv"));
+
+        std::fs::remove_dir_all(&path)?;
+        Ok(())
+    }
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::{settings::triggers::sonarr::SonarrRequest, settings::triggers::TriggerRequest};
+    use crate::settings::triggers::{sonarr::SonarrRequest, Trigger, TriggerPath, TriggerRequest};
+    use autopulse_database::models::EventKind;
 
     #[test]
     fn test_from_json_test() {
@@ -225,4 +226,69 @@ mod tests {
         assert!(matches!(sonarr_request, SonarrRequest::Other));
         assert_eq!(sonarr_request.paths(), vec![]);
     }
+
+    #[test]
+    fn test_events_rename_carries_previous_path() {
+        let json = serde_json::json!({
+            "eventType": "Rename",
+            "series": { "path": "/TV/Westworld" },
+            "renamedEpisodeFiles": [
+                {
+                    "previousPath": "/TV/Westworld/Season 1/old.mkv",
+                    "relativePath": "Season 1/new.mkv"
+                }
+            ]
+        });
+
+        let sonarr_request = SonarrRequest::from_json(json).unwrap();
+
+        assert_eq!(
+            sonarr_request.events(),
+            vec![
+                TriggerPath::new(
+                    "/TV/Westworld/Season 1/old.mkv".to_string(),
+                    EventKind::Deleted
+                ),
+                TriggerPath::renamed(
+                    "/TV/Westworld/Season 1/new.mkv".to_string(),
+                    "/TV/Westworld/Season 1/old.mkv".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trigger_paths_drops_delete_of_upgraded_file() {
+        let trigger: Trigger = serde_json::from_value(serde_json::json!({
+            "type": "sonarr"
+        }))
+        .unwrap();
+
+        let (event_name, events) = trigger
+            .paths(serde_json::json!({
+                "eventType": "Download",
+                "episodeFile": { "relativePath": "Season 1/S01E01.mkv" },
+                "deletedFiles": [
+                    { "relativePath": "Season 1/S01E01.mkv" },
+                    { "relativePath": "Season 1/S01E01.avi" }
+                ],
+                "series": { "path": "/TV/Westworld" }
+            }))
+            .unwrap();
+
+        assert_eq!(event_name, "Download");
+        assert_eq!(
+            events,
+            vec![
+                TriggerPath::new(
+                    "/TV/Westworld/Season 1/S01E01.mkv".to_string(),
+                    EventKind::Created
+                ),
+                TriggerPath::new(
+                    "/TV/Westworld/Season 1/S01E01.avi".to_string(),
+                    EventKind::Deleted
+                ),
+            ]
+        );
+    }
 }
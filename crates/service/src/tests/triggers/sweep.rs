@@ -0,0 +1,77 @@
+#![cfg(test)]
+mod tests {
+    use crate::settings::triggers::sweep::Sweep;
+    use autopulse_utils::generate_uuid;
+    use std::{env, fs};
+
+    fn sweep(root: &std::path::Path, extra: serde_json::Value) -> Sweep {
+        let mut value = serde_json::json!({
+            "schedule": "*/5 * * * *",
+            "paths": [root.to_string_lossy()],
+        });
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+
+        serde_json::from_value(value).expect("sweep trigger should deserialize")
+    }
+
+    #[test]
+    fn test_scan_honours_depth_extensions_and_rewrite() -> anyhow::Result<()> {
+        let root = env::temp_dir().join(generate_uuid());
+        fs::create_dir_all(root.join("Show/Season 1"))?;
+        fs::write(root.join("movie.mkv"), b"")?;
+        fs::write(root.join("movie.nfo"), b"")?;
+        fs::write(root.join("Show/Season 1/e01.mkv"), b"")?;
+
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1);
+        let root_str = root.to_string_lossy().to_string();
+
+        let shallow = sweep(
+            &root,
+            serde_json::json!({
+                "depth": 1,
+                "extensions": ["mkv"],
+                "rewrite": { "from": root_str, "to": "/media" },
+            }),
+        );
+        assert_eq!(shallow.scan(since)?, vec!["/media/movie.mkv".to_string()]);
+
+        let mut deep = sweep(&root, serde_json::json!({ "extensions": [".MKV"] })).scan(since)?;
+        deep.sort();
+        assert_eq!(
+            deep,
+            vec![
+                root.join("Show/Season 1/e01.mkv")
+                    .to_string_lossy()
+                    .to_string(),
+                root.join("movie.mkv").to_string_lossy().to_string(),
+            ]
+        );
+
+        let later = chrono::Utc::now().naive_utc() + chrono::Duration::minutes(1);
+        assert!(sweep(&root, serde_json::json!({})).scan(later)?.is_empty());
+
+        fs::remove_dir_all(&root)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_accepts_five_fields_and_rejects_garbage() {
+        let root = env::temp_dir();
+
+        assert!(sweep(&root, serde_json::json!({})).validate().is_ok());
+        assert!(
+            sweep(&root, serde_json::json!({ "schedule": "0 30 3 * * *" }))
+                .validate()
+                .is_ok()
+        );
+
+        let err = sweep(&root, serde_json::json!({ "schedule": "every day" }))
+            .validate()
+            .expect_err("invalid cron should fail");
+        assert!(err.to_string().contains("invalid sweep schedule"), "{err}");
+    }
+}
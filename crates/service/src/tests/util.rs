@@ -16,8 +16,11 @@ pub fn unique_db_url(scope: &str) -> String {
 }
 
 pub fn fresh_manager(scope: &str) -> PulseManager {
+    fresh_manager_with(scope, Settings::default())
+}
+
+pub fn fresh_manager_with(scope: &str, mut settings: Settings) -> PulseManager {
     let url = unique_db_url(scope);
-    let mut settings = Settings::default();
     settings.app.database_url = url.clone();
     let pool = get_pool(&url).expect("test database pool should initialize");
     get_conn(&pool)
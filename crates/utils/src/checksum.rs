@@ -1,25 +1,145 @@
 use sha2::{Digest, Sha256};
 use std::{
+    fmt::Display,
     fs::File,
     io::{BufReader, Read},
-    path::PathBuf,
+    path::Path,
+    str::FromStr,
 };
 
-pub fn sha256checksum(file_path: &PathBuf) -> anyhow::Result<String> {
+/// Algorithm of a file hash, written as a `<algorithm>:` prefix on the hash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha1,
+    Md5,
+    Xxh3,
+    Blake3,
+}
+
+impl From<HashAlgorithm> for &'static str {
+    fn from(val: HashAlgorithm) -> Self {
+        match val {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Xxh3 => "xxh3",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <&'static str>::from(*self))
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "sha1" => Ok(Self::Sha1),
+            "md5" => Ok(Self::Md5),
+            "xxh3" => Ok(Self::Xxh3),
+            "blake3" => Ok(Self::Blake3),
+            _ => Err(anyhow::anyhow!(
+                "unknown hash algorithm '{s}', expected one of sha256, sha1, md5, xxh3 or blake3"
+            )),
+        }
+    }
+}
+
+/// A hash a file is expected to match, e.g. `xxh3:9a4f3b2c1d0e8f76`.
+///
+/// Hashes without a prefix are SHA-256.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileHash {
+    pub algorithm: HashAlgorithm,
+    /// Lowercase hex digest
+    pub digest: String,
+}
+
+impl FromStr for FileHash {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, digest) = match s.trim().split_once(':') {
+            Some((algorithm, digest)) => (algorithm.parse()?, digest),
+            None => (HashAlgorithm::Sha256, s.trim()),
+        };
+
+        if digest.is_empty() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("invalid {algorithm} hash '{digest}'"));
+        }
+
+        Ok(Self {
+            algorithm,
+            digest: digest.to_ascii_lowercase(),
+        })
+    }
+}
+
+impl Display for FileHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.digest)
+    }
+}
+
+fn read_chunks(file_path: &Path, mut update: impl FnMut(&[u8])) -> anyhow::Result<()> {
     let file = File::open(file_path)?;
     let mut reader = BufReader::new(file);
-    let mut hasher = Sha256::new();
-    let mut buffer = [0u8; 8192];
+    let mut buffer = vec![0u8; 1 << 16];
 
     loop {
         let bytes_read = reader.read(&mut buffer)?;
         if bytes_read == 0 {
             break;
         }
-        hasher.update(&buffer[..bytes_read]);
+        update(&buffer[..bytes_read]);
     }
 
-    let hash = hasher.finalize();
+    Ok(())
+}
+
+fn digest_of<D: Digest>(file_path: &Path) -> anyhow::Result<String> {
+    let mut hasher = D::new();
+    read_chunks(file_path, |chunk| hasher.update(chunk))?;
+
+    Ok(base16ct::lower::encode_string(&hasher.finalize()))
+}
+
+/// Lowercase hex digest of a file.
+pub fn checksum(file_path: &Path, algorithm: HashAlgorithm) -> anyhow::Result<String> {
+    match algorithm {
+        HashAlgorithm::Sha256 => digest_of::<Sha256>(file_path),
+        HashAlgorithm::Sha1 => digest_of::<sha1::Sha1>(file_path),
+        HashAlgorithm::Md5 => digest_of::<md5::Md5>(file_path),
+        HashAlgorithm::Xxh3 => {
+            let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+            read_chunks(file_path, |chunk| hasher.update(chunk))?;
+
+            Ok(format!("{:016x}", hasher.digest()))
+        }
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            read_chunks(file_path, |chunk| {
+                hasher.update(chunk);
+            })?;
+
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+    }
+}
+
+pub fn sha256checksum(file_path: &Path) -> anyhow::Result<String> {
+    checksum(file_path, HashAlgorithm::Sha256)
+}
 
-    Ok(base16ct::lower::encode_string(&hash))
+/// Lowercase hex MD5 digest of `data`.
+pub fn md5_hex(data: &[u8]) -> String {
+    base16ct::lower::encode_string(&md5::Md5::digest(data))
 }
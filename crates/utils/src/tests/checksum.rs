@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::checksum::sha256checksum;
+    use crate::checksum::{checksum, sha256checksum, FileHash, HashAlgorithm};
     use std::{
         env,
         fs::{remove_file, File},
@@ -41,4 +41,51 @@ mod tests {
             "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
         );
     }
+
+    #[test]
+    fn test_checksum_algorithms() {
+        let tmp_dir = env::temp_dir();
+        let path = tmp_dir.join("test_checksum_algorithms.txt");
+
+        let mut file = File::create(&path).unwrap();
+        file.write_all(b"hi!").unwrap();
+
+        let digests = [
+            HashAlgorithm::Sha1,
+            HashAlgorithm::Md5,
+            HashAlgorithm::Xxh3,
+            HashAlgorithm::Blake3,
+        ]
+        .map(|algorithm| checksum(&path, algorithm).unwrap());
+
+        remove_file(&path).unwrap();
+
+        assert_eq!(
+            digests,
+            [
+                "3a987acf8cbc1028b7dbc86bd086831151899a2b",
+                "aff97160474a056e838c1f721af01edf",
+                "1d96a7cbbcf6a6fd",
+                "2a66e9faaa543ba88df1ca12549f1acd7d8b9325ae04ee24392d85782e9c4bc8",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_hash_prefix() {
+        assert_eq!(
+            "XXH3:9A4F".parse::<FileHash>().unwrap(),
+            FileHash {
+                algorithm: HashAlgorithm::Xxh3,
+                digest: "9a4f".to_string(),
+            }
+        );
+        assert_eq!(
+            "c0ddd6".parse::<FileHash>().unwrap().algorithm,
+            HashAlgorithm::Sha256,
+            "unprefixed hashes stay sha256"
+        );
+        assert!("crc32:deadbeef".parse::<FileHash>().is_err());
+        assert!("md5:not-hex".parse::<FileHash>().is_err());
+    }
 }
@@ -12,13 +12,13 @@
 #![doc = include_str!("../README.md")]
 
 use anyhow::Context;
-use autopulse_database::conn::{close_pool, get_conn, get_pool, AnyConnection};
+use autopulse_database::conn::{close_pool, copy_database, get_conn, get_pool, AnyConnection};
 use autopulse_server::get_server;
 use autopulse_service::manager::PulseManager;
 use autopulse_service::settings::{opts::Opts, Settings};
 use autopulse_utils::tracing_appender::non_blocking::WorkerGuard;
 use autopulse_utils::{setup_logs, Rotation};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tracing::{debug, error, info, warn};
 
 /// Arguments for CLI
@@ -32,6 +32,28 @@ pub struct Args {
     /// Location of configuration file
     #[arg(short, long)]
     pub config: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands run instead of starting the server
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Copy all events and state into another database, e.g. when moving from SQLite to Postgres
+    ///
+    /// Stop autopulse first, then point `app.database_url` at the new database once done.
+    MigrateDb {
+        /// Database to copy into, it must not hold any events yet
+        #[arg(long)]
+        to: String,
+        /// Database to copy from, defaults to `app.database_url`
+        #[arg(long)]
+        from: Option<String>,
+        /// Number of rows read and written at a time
+        #[arg(long, default_value_t = 500)]
+        batch_size: i64,
+    },
 }
 
 fn on_shutdown() -> tokio::task::JoinHandle<anyhow::Result<()>> {
@@ -96,6 +118,8 @@ async fn run(settings: Settings, _guard: Option<WorkerGuard>) -> anyhow::Result<
     let handle_events_task = manager.start();
     let handle_webhooks_task = manager.start_webhooks();
     let handle_notify_task = manager.start_notify();
+    let handle_sweeps_task = manager.start_sweeps();
+    let handle_bus_task = manager.start_bus();
 
     let server = get_server(&hostname, &port, manager.clone())?;
 
@@ -114,6 +138,12 @@ async fn run(settings: Settings, _guard: Option<WorkerGuard>) -> anyhow::Result<
         res = handle_notify_task => {
             res?;
         }
+        res = handle_sweeps_task => {
+            res?;
+        }
+        res = handle_bus_task => {
+            res?;
+        }
         res = server => {
             res?;
         }
@@ -124,11 +154,66 @@ async fn run(settings: Settings, _guard: Option<WorkerGuard>) -> anyhow::Result<
     Ok(())
 }
 
-#[doc(hidden)]
-fn setup() -> anyhow::Result<(Settings, Option<WorkerGuard>)> {
-    let args = Args::parse();
+fn migrate_db(
+    settings: &Settings,
+    from: Option<String>,
+    to: String,
+    batch_size: i64,
+) -> anyhow::Result<()> {
+    let from = from.unwrap_or_else(|| settings.app.database_url.clone());
+
+    if from == to {
+        anyhow::bail!("source and destination are the same database");
+    }
+
+    // The source is only read, it may well be a read-only replica or file
+    if let Some(path) = from.strip_prefix("sqlite://") {
+        if !std::path::Path::new(path).exists() {
+            anyhow::bail!("source database {path} does not exist");
+        }
+    }
+
+    AnyConnection::pre_init(&to)?;
+
+    let src_pool = get_pool(&from).context("failed to open source database")?;
+    let dst_pool = get_pool(&to).context("failed to open destination database")?;
+
+    let mut src = get_conn(&src_pool)?;
+    let mut dst = get_conn(&dst_pool)?;
+
+    let behind = src
+        .migrations_behind()
+        .context("failed to check migrations on source database")?;
 
-    let loaded = Settings::get_settings(args.config).context("failed to load settings");
+    if behind > 0 {
+        anyhow::bail!(
+            "source database has {behind} pending migrations, start autopulse on it once to upgrade it before copying"
+        );
+    }
+
+    dst.migrate()
+        .context("failed to run migrations on destination database")?;
+
+    info!("📦 copying database...");
+
+    copy_database(&mut src, &mut dst, batch_size, |table, rows| {
+        info!("{table}: {rows} rows copied")
+    })?;
+
+    drop(src);
+    drop(dst);
+
+    close_pool(&src_pool);
+    close_pool(&dst_pool);
+
+    info!("✅ database copied, point `app.database_url` at the destination to use it");
+
+    Ok(())
+}
+
+#[doc(hidden)]
+fn setup(config: Option<String>) -> anyhow::Result<(Settings, Option<WorkerGuard>)> {
+    let loaded = Settings::get_settings(config).context("failed to load settings");
 
     match loaded {
         Ok(loaded) => {
@@ -160,8 +245,25 @@ fn setup() -> anyhow::Result<(Settings, Option<WorkerGuard>)> {
 
 #[doc(hidden)]
 pub fn main() -> anyhow::Result<()> {
-    match setup() {
-        Ok((settings, guard)) => {
+    let args = Args::parse();
+
+    match (setup(args.config), args.command) {
+        (
+            Ok((settings, guard)),
+            Some(Command::MigrateDb {
+                to,
+                from,
+                batch_size,
+            }),
+        ) => {
+            if let Err(e) = migrate_db(&settings, from, to, batch_size) {
+                error!("{:?}", e);
+                // `exit` skips destructors, flush the log file first
+                drop(guard);
+                std::process::exit(1);
+            }
+        }
+        (Ok((settings, guard)), None) => {
             info!("💫 autopulse v{} starting up...", env!("CARGO_PKG_VERSION"),);
             settings.log_summary();
 
@@ -169,7 +271,7 @@ pub fn main() -> anyhow::Result<()> {
                 error!("{:?}", e);
             }
         }
-        Err(e) => {
+        (Err(e), _) => {
             error!("{:?}", e);
         }
     }